@@ -2,6 +2,7 @@
 
 use clap::{App, Arg};
 use ipmpsc::{Receiver, SharedRingBuffer};
+use std::ops::ControlFlow;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let matches = App::new("ipmpsc-send")
@@ -16,6 +17,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 )
                 .required(true),
         )
+        .arg(
+            Arg::with_name("bytes")
+                .long("bytes")
+                .conflicts_with("zero copy")
+                .help(
+                    "Print the raw bytes of each message rather than deserializing it as a string",
+                ),
+        )
         .arg(
             Arg::with_name("zero copy")
                 .long("zero-copy")
@@ -24,7 +33,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .get_matches();
 
     let map_file = matches.value_of("map file").unwrap();
-    let mut rx = Receiver::new(SharedRingBuffer::create(map_file, 32 * 1024)?);
+    let rx = Receiver::new(SharedRingBuffer::create(map_file, 32 * 1024)?);
     let zero_copy = matches.is_present("zero copy");
 
     println!(
@@ -32,11 +41,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         map_file
     );
 
-    loop {
-        if zero_copy {
-            println!("received {:?}", rx.zero_copy_context().recv::<&str>()?);
-        } else {
-            println!("received {:?}", rx.recv::<String>()?);
+    if matches.is_present("bytes") {
+        for bytes in rx.byte_iter() {
+            println!("received {:?}", bytes?);
         }
+    } else if zero_copy {
+        rx.run_zero_copy(|context| {
+            println!("received {:?}", context.recv::<&str>()?);
+            Ok(ControlFlow::Continue(()))
+        })?;
+    } else {
+        rx.run(|message: String| {
+            println!("received {:?}", message);
+            ControlFlow::Continue(())
+        })?;
     }
+
+    println!("All senders have disconnected.");
+
+    Ok(())
 }
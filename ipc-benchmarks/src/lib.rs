@@ -42,12 +42,25 @@ mod tests {
     use super::*;
     use anyhow::{anyhow, Error, Result};
     use ipc_channel::ipc;
-    use ipmpsc::{Receiver, Sender, SharedRingBuffer};
+    use ipmpsc::{Receiver, Sender, SharedRingBuffer, SharedRingBufferBuilder, SpscSender};
+    use std::{env, path::PathBuf};
     use test::Bencher;
 
     const SMALL: (usize, usize) = (3, 2);
     const LARGE: (usize, usize) = (3840, 2160);
 
+    /// Returns the directory benchmarks should create ring buffers in: `$IPMPSC_TEST_TMPDIR` if set, or the default
+    /// temporary directory otherwise (see the README).
+    fn temp_dir() -> PathBuf {
+        env::var_os("IPMPSC_TEST_TMPDIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir)
+    }
+
+    fn create_temp(size_in_bytes: u32) -> ipmpsc::Result<(String, SharedRingBuffer)> {
+        SharedRingBufferBuilder::new(size_in_bytes).create_temp_in(temp_dir())
+    }
+
     fn y_stride(width: usize) -> usize {
         width
     }
@@ -58,24 +71,46 @@ mod tests {
 
     #[bench]
     fn bench_ipmpsc_small(bencher: &mut Bencher) -> Result<()> {
-        bench_ipmpsc(bencher, SMALL)
+        bench_ipmpsc(bencher, SMALL, false)
     }
 
     #[bench]
     fn bench_ipmpsc_large(bencher: &mut Bencher) -> Result<()> {
-        bench_ipmpsc(bencher, LARGE)
+        bench_ipmpsc(bencher, LARGE, false)
+    }
+
+    #[bench]
+    fn bench_ipmpsc_spsc_small(bencher: &mut Bencher) -> Result<()> {
+        bench_ipmpsc(bencher, SMALL, true)
     }
 
-    fn bench_ipmpsc(bencher: &mut Bencher, (width, height): (usize, usize)) -> Result<()> {
-        let (name, buffer) = SharedRingBuffer::create_temp(32 * 1024 * 1024)?;
+    #[bench]
+    fn bench_ipmpsc_spsc_large(bencher: &mut Bencher) -> Result<()> {
+        bench_ipmpsc(bencher, LARGE, true)
+    }
+
+    fn bench_ipmpsc(
+        bencher: &mut Bencher,
+        (width, height): (usize, usize),
+        spsc: bool,
+    ) -> Result<()> {
+        let (name, buffer) = SharedRingBufferBuilder::new(32 * 1024 * 1024)
+            .single_producer(spsc)
+            .create_temp_in(temp_dir())?;
         let mut rx = Receiver::new(buffer);
 
-        let (exit_name, exit_buffer) = SharedRingBuffer::create_temp(1)?;
+        let (exit_name, exit_buffer) = create_temp(1)?;
         let exit_tx = Sender::new(exit_buffer);
 
         let sender = ipmpsc::fork(move || {
             let buffer = SharedRingBuffer::open(&name)?;
-            let tx = Sender::new(buffer);
+            let send: Box<dyn Fn(&YuvFrame) -> ipmpsc::Result<bool>> = if spsc {
+                let tx = SpscSender::new(buffer)?;
+                Box::new(move |frame| tx.send_timeout(frame, Duration::from_millis(100)))
+            } else {
+                let tx = Sender::new(buffer);
+                Box::new(move |frame| tx.send_timeout(frame, Duration::from_millis(100)))
+            };
 
             let exit_buffer = SharedRingBuffer::open(&exit_name)?;
             let exit_rx = Receiver::new(exit_buffer);
@@ -98,7 +133,7 @@ mod tests {
             };
 
             while exit_rx.try_recv::<u8>()?.is_none() {
-                tx.send_timeout(&frame, Duration::from_millis(100))?;
+                send(&frame)?;
             }
 
             Ok(())
@@ -140,7 +175,7 @@ mod tests {
     fn bench_ipc_channel(bencher: &mut Bencher, (width, height): (usize, usize)) -> Result<()> {
         let (tx, rx) = ipc::channel()?;
 
-        let (exit_name, exit_buffer) = SharedRingBuffer::create_temp(1)?;
+        let (exit_name, exit_buffer) = create_temp(1)?;
         let exit_tx = Sender::new(exit_buffer);
 
         let sender = ipmpsc::fork(move || {
@@ -212,7 +247,7 @@ mod tests {
     ) -> Result<()> {
         let (tx, rx) = ipc::bytes_channel()?;
 
-        let (exit_name, exit_buffer) = SharedRingBuffer::create_temp(1)?;
+        let (exit_name, exit_buffer) = create_temp(1)?;
         let exit_tx = Sender::new(exit_buffer);
 
         let sender = ipmpsc::fork(move || {
@@ -0,0 +1,358 @@
+use crate::{Error, Receiver, Result, Sender, SharedRingBuffer, WaitingReceiver};
+use futures_core::Stream;
+use futures_sink::Sink;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering::{Relaxed, SeqCst},
+        },
+        Arc, Condvar, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+/// How long the [`AsyncSender`](struct.AsyncSender.html) helper thread waits for space before checking whether its
+/// owner has been dropped.
+const SEND_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+struct State {
+    waker: Option<Waker>,
+    error: Option<Error>,
+    failed: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    condition: Condvar,
+    closed: AtomicBool,
+}
+
+impl Shared {
+    /// Body of the helper thread which blocks on the ring buffer on behalf of an async task, waking that task's
+    /// `Waker` whenever a message becomes available.
+    fn watch(&self, ring: &SharedRingBuffer) {
+        loop {
+            let waker = {
+                let mut state = self.state.lock().unwrap();
+                while state.waker.is_none() && !self.closed.load(SeqCst) {
+                    state = self.condition.wait(state).unwrap();
+                }
+
+                if self.closed.load(SeqCst) {
+                    break;
+                }
+
+                state.waker.take().unwrap()
+            };
+
+            if let Err(e) = self.wait_readable(ring) {
+                let mut state = self.state.lock().unwrap();
+                state.error = Some(e);
+                state.failed = true;
+                drop(state);
+                waker.wake();
+                break;
+            }
+
+            waker.wake();
+        }
+    }
+
+    fn wait_readable(&self, ring: &SharedRingBuffer) -> Result<()> {
+        let buffer = ring.0.buffer();
+        let _waiting = WaitingReceiver::new(buffer.header());
+        let mut lock = buffer.lock()?;
+        while !self.closed.load(SeqCst)
+            && buffer.header().read.load(Relaxed) == buffer.header().write.load(SeqCst)
+            && !ring.senders_disconnected()
+        {
+            lock.wait(&ring.0)?;
+        }
+        Ok(())
+    }
+
+    fn close(&self, ring: &SharedRingBuffer) {
+        self.closed.store(true, SeqCst);
+        self.condition.notify_all();
+
+        let buffer = ring.0.buffer();
+        if let Ok(mut lock) = buffer.lock() {
+            let _ = lock.notify_all();
+        }
+    }
+}
+
+/// Asynchronous wrapper around a [`Receiver`](struct.Receiver.html) which implements
+/// [`futures::Stream`](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html).
+///
+/// Waiting for a message never blocks an executor thread.  Instead, a dedicated helper thread blocks on the ring
+/// buffer on behalf of the task and wakes it when a message arrives.  This adds a thread handoff (typically a few
+/// microseconds) to the latency of the synchronous [`Receiver::recv`](struct.Receiver.html#method.recv), but only
+/// when the stream is polled while the ring buffer is empty.
+///
+/// Messages are deserialized into owned values; use the synchronous
+/// [`Receiver::zero_copy_context`](struct.Receiver.html#method.zero_copy_context) for zero-copy deserialization.
+pub struct AsyncReceiver<T> {
+    receiver: Receiver,
+    shared: Arc<Shared>,
+    _message: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> AsyncReceiver<T> {
+    /// Constructs an [`AsyncReceiver`](struct.AsyncReceiver.html) from the specified
+    /// [`Receiver`](struct.Receiver.html), spawning its helper thread.
+    pub fn new(receiver: Receiver) -> Self {
+        let shared = Arc::new(Shared::default());
+
+        thread::spawn({
+            let shared = shared.clone();
+            let ring = receiver.buffer.clone();
+            move || shared.watch(&ring)
+        });
+
+        Self {
+            receiver,
+            shared,
+            _message: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> AsyncReceiver<T> {
+    /// Attempt to receive a message without blocking, arranging for the current task to be woken when one becomes
+    /// available if none is available yet.
+    ///
+    /// This is the primitive underlying the [`Stream`](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html)
+    /// implementation and may be used directly to integrate with any executor.
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Result<T>> {
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            if state.failed {
+                return Poll::Ready(Err(state.error.take().unwrap_or_else(|| {
+                    Error::Runtime("AsyncReceiver helper thread has exited".into())
+                })));
+            }
+        }
+
+        match self.receiver.try_recv() {
+            Ok(Some(value)) => Poll::Ready(Ok(value)),
+            Ok(None) => {
+                self.register_waker(cx.waker());
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    /// Receive a message, waiting asynchronously if necessary until one becomes available.
+    ///
+    /// This works with any executor, including Tokio: `receiver.recv().await` never blocks a runtime worker thread,
+    /// since any blocking happens on this receiver's helper thread instead (see above).  When a message is already
+    /// available, it is received immediately without involving the helper thread at all, so the latency matches
+    /// that of [`Receiver::try_recv`](struct.Receiver.html#method.try_recv).
+    pub async fn recv(&self) -> Result<T> {
+        future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Register a `Waker` to be woken (once) as soon as the ring buffer is non-empty, replacing any previously
+    /// registered `Waker`.
+    ///
+    /// If the ring buffer is already non-empty, the `Waker` is woken immediately.
+    pub fn register_waker(&self, waker: &Waker) {
+        self.shared.state.lock().unwrap().waker = Some(waker.clone());
+        self.shared.condition.notify_one();
+    }
+}
+
+impl<T: DeserializeOwned> Stream for AsyncReceiver<T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        {
+            // If the helper thread failed and we've already reported its error, end the stream since nothing is
+            // left to wake us up.
+            let state = self.shared.state.lock().unwrap();
+            if state.failed && state.error.is_none() {
+                return Poll::Ready(None);
+            }
+        }
+
+        match self.poll_recv(cx) {
+            // The stream ends once all senders have disconnected (or the ring buffer has been shut down) and every
+            // message has been received.
+            Poll::Ready(Err(Error::NoSenders)) | Poll::Ready(Err(Error::Interrupted)) => {
+                Poll::Ready(None)
+            }
+            poll => poll.map(Some),
+        }
+    }
+}
+
+impl<T> Drop for AsyncReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.close(&self.receiver.buffer);
+    }
+}
+
+#[derive(Default)]
+struct SendState {
+    message: Option<Vec<u8>>,
+    result: Option<Result<()>>,
+    waker: Option<Waker>,
+}
+
+#[derive(Default)]
+struct SendShared {
+    state: Mutex<SendState>,
+    condition: Condvar,
+    closed: AtomicBool,
+}
+
+impl SendShared {
+    /// Body of the helper thread which performs blocking sends on behalf of an async task when the ring buffer is
+    /// full, waking that task's `Waker` once the message has been written.
+    fn send_blocking(&self, sender: &Sender) {
+        loop {
+            let message = {
+                let mut state = self.state.lock().unwrap();
+                while state.message.is_none() && !self.closed.load(SeqCst) {
+                    state = self.condition.wait(state).unwrap();
+                }
+
+                if self.closed.load(SeqCst) {
+                    break;
+                }
+
+                state.message.take().unwrap()
+            };
+
+            let result = loop {
+                match sender.send_serialized_timeout_0(&message, Some(SEND_POLL_INTERVAL)) {
+                    Ok(None) | Err(Error::LockTimeout) if !self.closed.load(SeqCst) => continue,
+                    Ok(_) => break Ok(()),
+                    Err(e) => break Err(e),
+                }
+            };
+
+            let mut state = self.state.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Asynchronous wrapper around a [`Sender`](struct.Sender.html) which implements
+/// [`futures::Sink`](https://docs.rs/futures/0.3/futures/sink/trait.Sink.html).
+///
+/// Each message is serialized when it is passed to `start_send` and written to the ring buffer when the sink is
+/// flushed.  If there is not enough space, a dedicated helper thread waits for the receiver to make room so that
+/// executor threads are never blocked, and `poll_ready`/`poll_flush` return `Pending` until the message has been
+/// written, providing backpressure.
+pub struct AsyncSender<T> {
+    sender: Sender,
+    shared: Arc<SendShared>,
+    message: Option<Vec<u8>>,
+    in_flight: bool,
+    _message: PhantomData<fn(T)>,
+}
+
+impl<T: Serialize> AsyncSender<T> {
+    /// Constructs an [`AsyncSender`](struct.AsyncSender.html) from the specified
+    /// [`Sender`](struct.Sender.html), spawning its helper thread.
+    ///
+    /// The helper thread uses a clone of `sender`, so this will return
+    /// `Err(`[`Error::TooManySenders`](enum.Error.html#variant.TooManySenders)`)` if cloning it would exceed the
+    /// limit passed to [`Sender::with_max_clones`](struct.Sender.html#method.with_max_clones).
+    pub fn new(sender: Sender) -> Result<Self> {
+        let shared = Arc::new(SendShared::default());
+
+        thread::spawn({
+            let shared = shared.clone();
+            let sender = sender.try_clone()?;
+            move || shared.send_blocking(&sender)
+        });
+
+        Ok(Self {
+            sender,
+            shared,
+            message: None,
+            in_flight: false,
+            _message: PhantomData,
+        })
+    }
+
+    fn poll_flush_0(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if self.in_flight {
+            let mut state = self.shared.state.lock().unwrap();
+            return if let Some(result) = state.result.take() {
+                self.in_flight = false;
+                Poll::Ready(result)
+            } else {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            };
+        }
+
+        if let Some(message) = self.message.take() {
+            match self
+                .sender
+                .send_serialized_timeout_0(&message, Some(Duration::from_secs(0)))
+            {
+                Ok(Some(_)) => Poll::Ready(Ok(())),
+                // If there isn't space, or another sender is holding the lock, let the helper thread wait:
+                Ok(None) | Err(Error::LockTimeout) => {
+                    let mut state = self.shared.state.lock().unwrap();
+                    state.message = Some(message);
+                    state.waker = Some(cx.waker().clone());
+                    self.in_flight = true;
+                    self.shared.condition.notify_one();
+                    Poll::Pending
+                }
+                Err(e) => Poll::Ready(Err(e)),
+            }
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl<T: Serialize> Sink<T> for AsyncSender<T> {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut().poll_flush_0(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<()> {
+        let this = self.get_mut();
+        debug_assert!(this.message.is_none() && !this.in_flight);
+        this.message = Some(this.sender.config.serialize(&item)?);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut().poll_flush_0(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut().poll_flush_0(cx)
+    }
+}
+
+impl<T> Drop for AsyncSender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, SeqCst);
+        self.shared.condition.notify_all();
+    }
+}
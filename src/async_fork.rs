@@ -0,0 +1,63 @@
+use crate::{os, ChildError};
+use anyhow::Result;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+#[derive(Default)]
+struct State {
+    result: Option<Result<(), ChildError>>,
+    waker: Option<Waker>,
+}
+
+/// Future returned by [`fork_async`](fn.fork_async.html) which resolves to the outcome of the child process once
+/// it exits.
+///
+/// Dropping this before it resolves does not kill the child; its outcome is simply discarded.
+pub struct ChildFuture {
+    state: Arc<Mutex<State>>,
+}
+
+impl Future for ChildFuture {
+    type Output = Result<(), ChildError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            Poll::Ready(result)
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// Run `fun` in a child process, returning a [`ChildFuture`](struct.ChildFuture.html) which resolves once the
+/// child exits, reporting how it did so exactly as the synchronous [`fork`](fn.fork.html) would.
+///
+/// As with [`AsyncReceiver`](struct.AsyncReceiver.html), waiting never blocks an executor thread: a dedicated
+/// helper thread monitors the child and wakes the task when it exits, so no particular executor or reactor is
+/// required.
+pub fn fork_async<F: Send + 'static + FnOnce() -> Result<()>>(fun: F) -> Result<ChildFuture> {
+    let monitor = os::test::fork_0(fun)?;
+    let state = Arc::new(Mutex::new(State::default()));
+
+    thread::spawn({
+        let state = state.clone();
+        move || {
+            let result = monitor();
+
+            let mut state = state.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+    });
+
+    Ok(ChildFuture { state })
+}
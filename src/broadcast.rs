@@ -0,0 +1,206 @@
+use crate::{occupied, os::Lock, Error, Result, SharedRingBuffer, CONFIG_FINGERPRINT};
+use serde::Deserialize;
+use std::{
+    sync::atomic::Ordering::{Relaxed, Release, SeqCst},
+    time::{Duration, Instant},
+};
+
+/// Marks an unused slot in `Header::cursors`, which can't be mistaken for a read position since the data region
+/// starts after the header.
+pub const FREE: u32 = 0;
+
+/// Receiving end of a ring buffer in broadcast mode, where every message is delivered to each of several receivers
+/// (possibly in different processes) rather than to exactly one.
+///
+/// Each [`BroadcastReceiver`](struct.BroadcastReceiver.html) registers a slot in the ring buffer's header holding
+/// its own read position, of which there are [`MAX_BROADCAST_RECEIVERS`](constant.MAX_BROADCAST_RECEIVERS.html).
+/// Space occupied by a message is only reclaimed once every registered receiver has received it, so a slow
+/// receiver applies backpressure to senders (which wait for space as usual) rather than missing messages.  A
+/// receiver which stops receiving without being dropped will therefore eventually stall every sender, so
+/// receivers should be dropped promptly once they are no longer needed, which releases their slots.
+///
+/// Broadcast receivers must not be used alongside a [`Receiver`](struct.Receiver.html) for the same ring buffer,
+/// since each moves the read pointer according to its own notion of what has been received.  Receiving involves
+/// taking the ring buffer lock to reconcile the receivers' positions, so it is somewhat slower than receiving using
+/// a [`Receiver`](struct.Receiver.html).
+///
+/// Messages are deserialized using the default [`Config`](struct.Config.html), so senders must not change it.
+pub struct BroadcastReceiver {
+    buffer: SharedRingBuffer,
+    slot: usize,
+}
+
+impl BroadcastReceiver {
+    /// Constructs a [`BroadcastReceiver`](struct.BroadcastReceiver.html) for the specified
+    /// [`SharedRingBuffer`](struct.SharedRingBuffer.html), registering it to receive every message still in the
+    /// ring buffer (i.e. every message not yet received by all the receivers already registered, or every message
+    /// sent so far if there are none) and every message sent from now on.
+    ///
+    /// This will return `Err(`[`Error::TooManyReceivers`](enum.Error.html#variant.TooManyReceivers)`)` if
+    /// [`MAX_BROADCAST_RECEIVERS`](constant.MAX_BROADCAST_RECEIVERS.html) receivers are already registered.
+    pub fn new(buffer: SharedRingBuffer) -> Result<Self> {
+        let slot = {
+            let buffer = buffer.0.buffer();
+            let header = buffer.header();
+
+            // Register while holding the lock so the read pointer can't move before our slot is visible to other
+            // receivers.
+            let _lock = buffer.lock()?;
+
+            let slot = header
+                .cursors
+                .iter()
+                .position(|cursor| cursor.load(SeqCst) == FREE)
+                .ok_or(Error::TooManyReceivers)?;
+
+            header.cursors[slot].store(header.read.load(SeqCst), SeqCst);
+            header.receiver_alive.store(1, Release);
+
+            slot
+        };
+
+        Ok(Self { buffer, slot })
+    }
+
+    /// Attempt to read a message without blocking.
+    ///
+    /// This will return `Ok(None)` if there are no messages immediately available.
+    pub fn try_recv<T>(&self) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        Ok(
+            if let Some((message, position)) =
+                self.buffer
+                    .read_frame_0(self.cursor(), CONFIG_FINGERPRINT, |position| {
+                        self.advance(position)
+                    })?
+            {
+                let value = bincode::deserialize(&message)?;
+
+                self.advance(position)?;
+
+                Some(value)
+            } else {
+                None
+            },
+        )
+    }
+
+    /// Attempt to read a message, blocking if necessary until one becomes available.
+    pub fn recv<T>(&self) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        loop {
+            if let Some(value) = self.recv_timeout_0(None)? {
+                break Ok(value);
+            }
+        }
+    }
+
+    /// Attempt to read a message, blocking for up to the specified duration if necessary until one becomes
+    /// available.
+    pub fn recv_timeout<T>(&self, timeout: Duration) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.recv_timeout_0(Some(timeout))
+    }
+
+    fn recv_timeout_0<T>(&self, timeout: Option<Duration>) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        loop {
+            if let Some(value) = self.try_recv()? {
+                return Ok(Some(value));
+            }
+
+            let now = Instant::now();
+            if deadline.map(|deadline| deadline > now).unwrap_or(true) {
+                self.buffer
+                    .wait_0(self.cursor(), deadline.map(|deadline| deadline - now))?;
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Returns the position of the next message for this receiver.
+    fn cursor(&self) -> u32 {
+        self.buffer.0.buffer().header().cursors[self.slot].load(Relaxed)
+    }
+
+    /// Move this receiver's position to `position`, and then advance the read pointer to the position of the
+    /// receiver furthest behind, waking any senders waiting for space.
+    fn advance(&self, position: u32) -> Result<()> {
+        let buffer = self.buffer.0.buffer();
+        let header = buffer.header();
+
+        // Receivers advance the read pointer while holding the lock so that they agree on which of them is furthest
+        // behind, and so it never moves backward.
+        let mut lock = buffer.lock()?;
+
+        header.cursors[self.slot].store(position, SeqCst);
+
+        self.release(&mut lock, position)
+    }
+
+    /// Advance the read pointer to the position of the registered receiver furthest behind (or `default` if there
+    /// are none), waking any senders waiting for space.
+    ///
+    /// The lock must be held by the caller.  Every registered position lies between the read and write pointers, so
+    /// the one furthest behind is the one closest to the read pointer.
+    fn release(&self, lock: &mut Lock, default: u32) -> Result<()> {
+        let buffer = self.buffer.0.buffer();
+        let header = buffer.header();
+        let map_len = buffer.map().len();
+
+        let read = header.read.load(SeqCst);
+        let slowest = header
+            .cursors
+            .iter()
+            .map(|cursor| cursor.load(SeqCst))
+            .filter(|&cursor| cursor != FREE)
+            .min_by_key(|&cursor| occupied(read, cursor, map_len))
+            .unwrap_or(default);
+
+        if slowest != read {
+            header.read.store(slowest, SeqCst);
+
+            // Other receivers may be waiting on the condition variable too, so wake everyone rather than risk
+            // waking only a receiver (see `SharedRingBufferBuilder::wake_one`).
+            if header.waiting_senders.load(SeqCst) != 0 {
+                lock.notify_all()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for BroadcastReceiver {
+    fn drop(&mut self) {
+        let buffer = self.buffer.0.buffer();
+        let header = buffer.header();
+
+        if let Ok(mut lock) = buffer.lock() {
+            let position = header.cursors[self.slot].swap(FREE, SeqCst);
+
+            // If we were the last receiver, everything before our position has been received by everyone.
+            let _ = self.release(&mut lock, position);
+
+            if header
+                .cursors
+                .iter()
+                .all(|cursor| cursor.load(SeqCst) == FREE)
+            {
+                header.receiver_alive.store(0, Release);
+            }
+        } else {
+            header.cursors[self.slot].store(FREE, SeqCst);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{
+    Config, CreateOutcome, MutexKind, Receiver, Result, SharedRingBuffer, OPTION_SINGLE_PRODUCER,
+    OPTION_WAKE_ONE, OPTION_ZERO_SIZED,
+};
+use std::{fs::File, path::Path, sync::atomic::Ordering::Release};
+
+#[cfg(unix)]
+use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+/// Builder for [`SharedRingBuffer`](struct.SharedRingBuffer.html)s with non-default options.
+///
+/// [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create) and
+/// [`SharedRingBuffer::create_temp`](struct.SharedRingBuffer.html#method.create_temp) are equivalent to using this
+/// type with the default options.
+#[derive(Clone, Debug)]
+pub struct SharedRingBufferBuilder {
+    pub(crate) size_in_bytes: u32,
+    pub(crate) mutex_kind: MutexKind,
+    allow_zero_sized: bool,
+    single_producer: bool,
+    wake_one: bool,
+    pub(crate) max_messages: u32,
+    #[cfg(unix)]
+    mode: Option<u32>,
+}
+
+impl SharedRingBufferBuilder {
+    /// Constructs a [`SharedRingBufferBuilder`](struct.SharedRingBufferBuilder.html) with the specified capacity
+    /// and default options.
+    ///
+    /// See [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create) for the meaning of
+    /// `size_in_bytes`.
+    pub fn new(size_in_bytes: u32) -> Self {
+        Self {
+            size_in_bytes,
+            mutex_kind: MutexKind::default(),
+            allow_zero_sized: false,
+            single_producer: false,
+            wake_one: false,
+            max_messages: 0,
+            #[cfg(unix)]
+            mode: None,
+        }
+    }
+
+    /// Use the specified kind of mutex (default: [`MutexKind::Default`](enum.MutexKind.html#variant.Default)).
+    ///
+    /// Creating the ring buffer will fail with `Err(`[`Error::Runtime`](enum.Error.html#variant.Runtime)`)` if
+    /// `mutex_kind` is not supported on this platform.
+    pub fn mutex_kind(mut self, mutex_kind: MutexKind) -> Self {
+        self.mutex_kind = mutex_kind;
+        self
+    }
+
+    /// Allow messages which serialize to zero bytes, such as `()` or empty structs, to be sent (default: `false`).
+    ///
+    /// By default, sending such a message fails with
+    /// `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`)`, since it is more likely to
+    /// be a mistake than intentional.  With this option, zero-sized messages are delivered like any other, which is
+    /// useful for pure signaling (e.g. `sender.send(&())?` and `receiver.recv::<()>()?`).  Each occupies four bytes
+    /// of the ring buffer.
+    ///
+    /// This is a property of the ring buffer itself, so it applies to every sender, including those in other
+    /// processes.
+    pub fn allow_zero_sized(mut self, allow_zero_sized: bool) -> Self {
+        self.allow_zero_sized = allow_zero_sized;
+        self
+    }
+
+    /// Reserve the ring buffer for a single [`SpscSender`](struct.SpscSender.html), which avoids the ring buffer
+    /// lock on its fast path (default: `false`).
+    ///
+    /// Ordinary [`Sender`](struct.Sender.html)s may not be used with such a ring buffer; attempting to send with
+    /// one will return `Err(`[`Error::WrongSenderKind`](enum.Error.html#variant.WrongSenderKind)`)`.
+    pub fn single_producer(mut self, single_producer: bool) -> Self {
+        self.single_producer = single_producer;
+        self
+    }
+
+    /// When the receiver frees space in the ring buffer, wake only one waiting sender rather than all of them,
+    /// unless the ring buffer has become empty (default: `false`).
+    ///
+    /// Waking every waiting sender after each message is received causes a thundering herd under heavy
+    /// multi-producer contention, since typically only one of them can proceed and the rest go back to waiting.
+    /// With this option, the others remain asleep until the receiver frees more space.  The tradeoff is that a
+    /// woken sender whose message doesn't fit yet goes back to waiting without waking another whose message might
+    /// have fit, so senders of large messages may delay senders of small ones until more space is freed.
+    ///
+    /// Either way, waiting senders are not served in any particular order: which one is woken is up to the
+    /// operating system's scheduler.
+    pub fn wake_one(mut self, wake_one: bool) -> Self {
+        self.wake_one = wake_one;
+        self
+    }
+
+    /// Limit the number of messages which may be in the ring buffer at once to `max_messages` (default: zero,
+    /// meaning no limit beyond the ring buffer capacity).
+    ///
+    /// Once the limit is reached, senders wait for the receiver to consume a message just as they would wait for
+    /// space, even if there are bytes to spare.  See
+    /// [`SharedRingBuffer::create_bounded`](struct.SharedRingBuffer.html#method.create_bounded) for a convenient
+    /// way to size a ring buffer by message count.
+    ///
+    /// Like [`SharedRingBufferBuilder::allow_zero_sized`](#method.allow_zero_sized), this applies to every
+    /// sender.
+    pub fn max_messages(mut self, max_messages: u32) -> Self {
+        self.max_messages = max_messages;
+        self
+    }
+
+    /// Set the permissions of the file backing the ring buffer to `mode` when it is created, e.g. `0o660` to
+    /// allow processes running as other users in the file's group to open it.
+    ///
+    /// By default, files are created with permissions determined by the process umask (or `0o600` in the case of
+    /// temporary files), which may prevent processes running as other users from opening them.
+    ///
+    /// Any process which can open the file can read every message sent through the ring buffer, send messages of
+    /// its own, and corrupt the ring buffer so as to make the receiver fail, so grant access as narrowly as
+    /// possible: prefer a dedicated group shared by the communicating users (e.g. `0o660`) over making the file
+    /// accessible to everyone (e.g. `0o666`), particularly for sensitive data.  See also
+    /// [`SharedRingBuffer::set_mode`](struct.SharedRingBuffer.html#method.set_mode).
+    #[cfg(unix)]
+    pub fn mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Create a [`SharedRingBuffer`](struct.SharedRingBuffer.html) backed by a file with the specified name, as
+    /// with [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create).
+    pub fn create(&self, path: &str) -> Result<SharedRingBuffer> {
+        Ok(SharedRingBuffer::create_0(path, self)?.0)
+    }
+
+    /// Create a [`SharedRingBuffer`](struct.SharedRingBuffer.html) backed by a file with the specified name and
+    /// report whether an existing file was truncated, as with
+    /// [`SharedRingBuffer::create_reporting`](struct.SharedRingBuffer.html#method.create_reporting).
+    pub fn create_reporting(&self, path: &str) -> Result<(SharedRingBuffer, CreateOutcome)> {
+        SharedRingBuffer::create_0(path, self)
+    }
+
+    /// Attach to or create a [`SharedRingBuffer`](struct.SharedRingBuffer.html) backed by a file with the specified
+    /// name, as with [`SharedRingBuffer::create_or_resume`](struct.SharedRingBuffer.html#method.create_or_resume).
+    ///
+    /// These options only take effect if a new ring buffer is created.
+    pub fn create_or_resume(&self, path: &str) -> Result<SharedRingBuffer> {
+        SharedRingBuffer::create_or_resume_0(path, self)
+    }
+
+    /// Create a [`SharedRingBuffer`](struct.SharedRingBuffer.html) backed by a temporary file, as with
+    /// [`SharedRingBuffer::create_temp`](struct.SharedRingBuffer.html#method.create_temp).
+    pub fn create_temp(&self) -> Result<(String, SharedRingBuffer)> {
+        SharedRingBuffer::create_temp_0(None, None, self)
+    }
+
+    /// Create a [`SharedRingBuffer`](struct.SharedRingBuffer.html) backed by a temporary file whose name starts with
+    /// `prefix`, as with
+    /// [`SharedRingBuffer::create_temp_named`](struct.SharedRingBuffer.html#method.create_temp_named).
+    pub fn create_temp_named(&self, prefix: &str) -> Result<(String, SharedRingBuffer)> {
+        SharedRingBuffer::create_temp_0(None, Some(prefix), self)
+    }
+
+    /// Like [`SharedRingBufferBuilder::create_temp`](struct.SharedRingBufferBuilder.html#method.create_temp), but
+    /// creates the temporary file in the specified directory rather than the default one (e.g. `$TMPDIR`).
+    pub fn create_temp_in(&self, dir: impl AsRef<Path>) -> Result<(String, SharedRingBuffer)> {
+        SharedRingBuffer::create_temp_0(Some(dir.as_ref()), None, self)
+    }
+
+    /// Returns the per-buffer options to store in the header of a newly created ring buffer.
+    pub(crate) fn option_bits(&self) -> u32 {
+        let zero_sized = if self.allow_zero_sized {
+            OPTION_ZERO_SIZED
+        } else {
+            0
+        };
+
+        let single_producer = if self.single_producer {
+            OPTION_SINGLE_PRODUCER
+        } else {
+            0
+        };
+
+        let wake_one = if self.wake_one { OPTION_WAKE_ONE } else { 0 };
+
+        zero_sized | single_producer | wake_one
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn set_permissions(&self, file: &File) -> Result<()> {
+        if let Some(mode) = self.mode {
+            file.set_permissions(Permissions::from_mode(mode))?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn set_permissions(&self, _file: &File) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Builder for [`Receiver`](struct.Receiver.html)s with non-default options.
+///
+/// [`Receiver::new`](struct.Receiver.html#method.new) is equivalent to using this type with the default options.
+#[derive(Clone, Debug, Default)]
+pub struct ReceiverBuilder {
+    spin_count: u32,
+    config: Config,
+}
+
+impl ReceiverBuilder {
+    /// Constructs a [`ReceiverBuilder`](struct.ReceiverBuilder.html) with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Busy-wait for up to `spin_count` iterations for a message to arrive before blocking (default: 0).
+    ///
+    /// Spinning can reduce latency when messages arrive in quick succession, at the expense of CPU time, since
+    /// waking a blocked receiver requires a round trip through the OS scheduler.  While the receiver is spinning,
+    /// [`Sender`](struct.Sender.html)s also skip the system call they would otherwise make to wake it.
+    pub fn spin_count(mut self, spin_count: u32) -> Self {
+        self.spin_count = spin_count;
+        self
+    }
+
+    /// Deserialize messages using the specified bincode configuration (default: that of `bincode::serialize`).
+    ///
+    /// This must match the configuration of every [`Sender`](struct.Sender.html) for the ring buffer (see
+    /// [`Sender::with_config`](struct.Sender.html#method.with_config)).
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Construct a [`Receiver`](struct.Receiver.html) for the specified
+    /// [`SharedRingBuffer`](struct.SharedRingBuffer.html) using these options.
+    pub fn build(&self, buffer: SharedRingBuffer) -> Receiver {
+        buffer.0.buffer().header().receiver_alive.store(1, Release);
+
+        let receiver = Receiver {
+            buffer_id: buffer.buffer_id(),
+            buffer,
+            spin_count: self.spin_count,
+            config: self.config,
+        };
+
+        receiver.heartbeat();
+
+        receiver
+    }
+
+    /// Construct a [`Receiver`](struct.Receiver.html) for an existing ring buffer backed by a file with the
+    /// specified name using these options, without reinitializing it.
+    ///
+    /// See [`Receiver::attach`](struct.Receiver.html#method.attach) for details.
+    pub fn attach(&self, path: &str) -> Result<Receiver> {
+        Ok(self.build(SharedRingBuffer::open(path)?))
+    }
+}
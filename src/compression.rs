@@ -0,0 +1,49 @@
+use crate::{Error, Result, UNCOMPRESSED};
+use std::borrow::Cow;
+
+/// Marker byte for a message compressed using LZ4, prefixed with its uncompressed size.
+const LZ4: u8 = 1;
+
+/// Compression applied by a [`Sender`](struct.Sender.html) to the messages it sends (see
+/// [`Sender::with_compression`](struct.Sender.html#method.with_compression)).  Only available with the
+/// `compression` feature enabled.
+///
+/// Each message records whether it was compressed, so receivers detect and decompress compressed messages
+/// automatically, regardless of which senders used which compression.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Messages are stored as serialized.
+    #[default]
+    None,
+
+    /// Messages are compressed using LZ4, which is fast enough to be worthwhile even for modest gains in space.
+    /// A message which would not get smaller is stored as serialized instead.
+    Lz4,
+}
+
+/// Compress `serialized` as specified, returning the marker byte to store with the result, or `None` if it should
+/// be stored uncompressed (i.e. compression is disabled or wouldn't help).
+pub fn compress(compression: Compression, serialized: &[u8]) -> Option<(u8, Vec<u8>)> {
+    match compression {
+        Compression::None => None,
+        Compression::Lz4 => {
+            let compressed = lz4_flex::compress_prepend_size(serialized);
+            if compressed.len() < serialized.len() {
+                Some((LZ4, compressed))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Decompress `message` according to `marker`, borrowing it if it was stored uncompressed.
+pub fn decompress(marker: u8, message: &[u8]) -> Result<Cow<'_, [u8]>> {
+    match marker {
+        UNCOMPRESSED => Ok(Cow::Borrowed(message)),
+        LZ4 => lz4_flex::decompress_size_prepended(message)
+            .map(Cow::Owned)
+            .map_err(|_| Error::CorruptBuffer),
+        _ => Err(Error::CorruptBuffer),
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{Result, CONFIG_FINGERPRINT};
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Byte order of integers (and floats) in messages encoded using a [`Config`](struct.Config.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endian {
+    /// Least significant byte first, as used by `bincode::serialize`.
+    #[default]
+    Little,
+
+    /// Most significant byte first.
+    Big,
+}
+
+/// Encoding of integers (including the lengths of sequences and strings) in messages encoded using a
+/// [`Config`](struct.Config.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntEncoding {
+    /// Each integer occupies the full width of its type, and lengths are `u64`s, as used by `bincode::serialize`.
+    #[default]
+    Fixed,
+
+    /// Each integer occupies between one and 17 bytes depending on its value, as described in the documentation
+    /// for `bincode::config::VarintEncoding`.
+    Varint,
+}
+
+/// Bincode configuration used by a [`Sender`](struct.Sender.html) or [`Receiver`](struct.Receiver.html) to
+/// (de)serialize messages (see [`Sender::with_config`](struct.Sender.html#method.with_config) and
+/// [`Receiver::with_config`](struct.Receiver.html#method.with_config)).
+///
+/// The default is the configuration used by `bincode::serialize`, i.e. little-endian, fixed-width integers, which is
+/// usually the easiest for processes written in other languages to read and write.  Senders and receivers for a
+/// given ring buffer must agree on the configuration; with the `fingerprint` feature enabled, a receiver reports
+/// any disagreement as [`Error::ConfigMismatch`](enum.Error.html#variant.ConfigMismatch).
+///
+/// Trailing bytes are always allowed and there is no size limit beyond that of the ring buffer, as with
+/// `bincode::serialize`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Byte order of integers
+    pub endian: Endian,
+
+    /// Encoding of integers
+    pub int_encoding: IntEncoding,
+}
+
+/// Evaluate `$body` with `$options` bound to the bincode options corresponding to `$config`.
+///
+/// Each combination has a different type, so this can't be a function returning them.
+macro_rules! with_options {
+    ($config:expr, $options:ident => $body:expr) => {{
+        let options = bincode::DefaultOptions::new().allow_trailing_bytes();
+        match ($config.endian, $config.int_encoding) {
+            (Endian::Little, IntEncoding::Fixed) => {
+                let $options = options.with_little_endian().with_fixint_encoding();
+                $body
+            }
+            (Endian::Little, IntEncoding::Varint) => {
+                let $options = options.with_little_endian().with_varint_encoding();
+                $body
+            }
+            (Endian::Big, IntEncoding::Fixed) => {
+                let $options = options.with_big_endian().with_fixint_encoding();
+                $body
+            }
+            (Endian::Big, IntEncoding::Varint) => {
+                let $options = options.with_big_endian().with_varint_encoding();
+                $body
+            }
+        }
+    }};
+}
+
+impl Config {
+    /// Returns the fingerprint identifying this configuration, which is written ahead of each message when the
+    /// `fingerprint` feature is enabled (see `CONFIG_FINGERPRINT`).
+    pub(crate) fn fingerprint(self) -> u8 {
+        let mut fingerprint = CONFIG_FINGERPRINT;
+        if self.endian == Endian::Big {
+            fingerprint |= 0b01;
+        }
+        if self.int_encoding == IntEncoding::Varint {
+            fingerprint |= 0b10;
+        }
+        fingerprint
+    }
+
+    pub(crate) fn serialized_size(self, value: &impl Serialize) -> Result<u64> {
+        Ok(with_options!(self, options => options.serialized_size(value))?)
+    }
+
+    #[cfg(any(feature = "compression", feature = "async"))]
+    pub(crate) fn serialize(self, value: &impl Serialize) -> Result<Vec<u8>> {
+        Ok(with_options!(self, options => options.serialize(value))?)
+    }
+
+    pub(crate) fn serialize_into(self, writer: impl Write, value: &impl Serialize) -> Result<()> {
+        Ok(with_options!(self, options => options.serialize_into(writer, value))?)
+    }
+
+    pub(crate) fn deserialize<'a, T: Deserialize<'a>>(self, bytes: &'a [u8]) -> Result<T> {
+        Ok(with_options!(self, options => options.deserialize(bytes))?)
+    }
+
+    /// Deserialize a `T` from the beginning of `bytes`, returning it along with the remainder.
+    pub(crate) fn deserialize_prefix<'a, T: Deserialize<'a> + Serialize>(
+        self,
+        bytes: &'a [u8],
+    ) -> Result<(T, &'a [u8])> {
+        let value = self.deserialize::<T>(bytes)?;
+        let size = self.serialized_size(&value)? as usize;
+        Ok((value, &bytes[size..]))
+    }
+}
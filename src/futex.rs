@@ -0,0 +1,52 @@
+use crate::{Error, Result};
+use std::{os::raw::c_long, ptr, sync::atomic::AtomicU32, time::Duration};
+
+/// Block until `atomic` is woken using [`wake_all`](fn.wake_all.html), `timeout` elapses, or a spurious wakeup
+/// occurs, unless `atomic` no longer contains `expected`, in which case return immediately.
+///
+/// Note that we use shared (i.e. not `FUTEX_PRIVATE_FLAG`) futexes since the waker is generally in another process.
+pub fn wait(atomic: &AtomicU32, expected: u32, timeout: Option<Duration>) -> Result<()> {
+    #[allow(clippy::cast_lossless)]
+    let timeout = timeout.map(|timeout| libc::timespec {
+        tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+        tv_nsec: timeout.subsec_nanos() as c_long,
+    });
+
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            atomic as *const AtomicU32,
+            libc::FUTEX_WAIT,
+            expected,
+            timeout
+                .as_ref()
+                .map(|timeout| timeout as *const libc::timespec)
+                .unwrap_or(ptr::null()),
+        )
+    };
+
+    if result == 0 {
+        Ok(())
+    } else {
+        match errno() {
+            libc::EAGAIN | libc::EINTR | libc::ETIMEDOUT => Ok(()),
+            error => Err(Error::Runtime(format!("FUTEX_WAIT failed: {}", error))),
+        }
+    }
+}
+
+/// Wake all threads (in any process) blocked in [`wait`](fn.wait.html) on `atomic`.
+pub fn wake_all(atomic: &AtomicU32) {
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            atomic as *const AtomicU32,
+            libc::FUTEX_WAKE,
+            libc::c_int::MAX,
+        );
+    }
+}
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap_or(0)
+}
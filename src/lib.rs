@@ -4,6 +4,36 @@
 //! memory ring buffer.  It uses [bincode](https://github.com/TyOverby/bincode) for (de)serialization, including
 //! zero-copy deserialization, making it ideal for messages with large `&str` or `&[u8]` fields.  And it has a name
 //! that rolls right off the tongue.
+//!
+//! # Message framing
+//!
+//! By default, messages are encoded using bincode's default configuration (i.e. that of `bincode::serialize`):
+//! little-endian, fixed-width integers, with lengths of sequences and strings encoded as `u64`s.  Processes written
+//! in other languages may rely on this, but big-endian and variable-length integer encodings may be selected using
+//! [`Config`](struct.Config.html).  Messages encoded any other way (e.g. using protobuf) may be sent and received
+//! as raw bytes using [`Sender::send_bytes`](struct.Sender.html#method.send_bytes) and
+//! [`Receiver::recv_bytes`](struct.Receiver.html#method.recv_bytes).
+//!
+//! Each message is stored contiguously in the ring buffer as a frame consisting of:
+//!
+//! 1. A size word: a little-endian `u32` giving the number of bytes which follow it in the frame (or `u32::MAX` for a
+//!    zero-sized message, which has nothing else in its frame).  A size word of zero is a wrap marker, meaning the next
+//!    frame is at the beginning of the data region rather than here.
+//!
+//! 2. If the `fingerprint` feature is enabled, a single byte identifying the bincode configuration (see
+//!    [`Config`](struct.Config.html) and [`Error::ConfigMismatch`](enum.Error.html#variant.ConfigMismatch)).
+//!
+//! 3. If the `compression` feature is enabled, a single byte indicating how the message was compressed: zero if it
+//!    wasn't, or one for LZ4, in which case the message is prefixed with its uncompressed size as a little-endian `u32`
+//!    (see [`Compression`](enum.Compression.html)).
+//!
+//! 4. If the `checksum` feature is enabled, the CRC32 of the message as stored (i.e. after any compression) as a
+//!    little-endian `u32` (see [`Error::ChecksumMismatch`](enum.Error.html#variant.ChecksumMismatch)).
+//!
+//! 5. The serialized (and possibly compressed) message itself.
+//!
+//! The data region starts [`HEADER_SIZE`](constant.HEADER_SIZE.html) bytes into the shared memory file.  The layout
+//! of the header preceding it is platform-specific and not part of the public interface.
 
 #![deny(warnings)]
 
@@ -11,19 +41,38 @@ use memmap2::MmapMut;
 use os::{Buffer, Header, View};
 use serde::{Deserialize, Serialize};
 use std::{
-    cell::UnsafeCell,
+    borrow::Cow,
+    cell::{Cell, UnsafeCell},
+    collections::hash_map::RandomState,
+    convert::TryFrom,
     ffi::c_void,
+    fmt,
     fs::{File, OpenOptions},
-    mem,
+    hash::{BuildHasher, Hasher},
+    hint,
+    io::{self, Write},
+    iter, mem,
+    ops::{ControlFlow, Deref},
+    path::Path,
     sync::{
-        atomic::Ordering::{Acquire, Relaxed, Release},
+        atomic::{
+            AtomicU32, AtomicU64, AtomicUsize,
+            Ordering::{Acquire, Relaxed, Release, SeqCst},
+        },
         Arc,
     },
-    time::{Duration, Instant},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tempfile::NamedTempFile;
 use thiserror::Error as ThisError;
 
+#[cfg(unix)]
+use std::{
+    fs::{self, Permissions},
+    os::unix::fs::PermissionsExt,
+};
+
 #[cfg(unix)]
 mod posix;
 
@@ -33,6 +82,15 @@ use posix as os;
 #[cfg(windows)]
 mod bitmask;
 
+#[cfg(all(feature = "futex", target_os = "linux"))]
+mod futex;
+
+#[cfg(all(feature = "pollable", target_os = "linux"))]
+mod pollable;
+
+#[cfg(all(feature = "pollable", target_os = "linux"))]
+pub use pollable::PollableReceiver;
+
 #[cfg(windows)]
 mod windows;
 
@@ -42,6 +100,94 @@ use windows as os;
 #[cfg(feature = "fork")]
 pub use os::test::fork;
 
+/// Reason a child process spawned using [`fork`](fn.fork.html) did not complete successfully.
+#[cfg(any(test, feature = "fork"))]
+#[derive(ThisError, Debug)]
+pub enum ChildError {
+    /// The child's function returned an error, described (along with its causes, if any) by `message`.
+    #[error("child failed: {message}{}", format_stderr(.stderr))]
+    Failed { message: String, stderr: String },
+
+    /// The child panicked with the specified message.
+    #[error("child panicked: {message}{}", format_stderr(.stderr))]
+    Panicked { message: String, stderr: String },
+
+    /// The child exited with an unexpected nonzero status (e.g. by calling `std::process::exit` itself).
+    #[error("child exited with status {status}{}", format_stderr(.stderr))]
+    Exited { status: i32, stderr: String },
+
+    /// The child was killed by the specified signal.
+    #[error("child killed by signal {signal}{}", format_stderr(.stderr))]
+    Signaled { signal: i32, stderr: String },
+
+    /// An error occurred while monitoring the child, so its outcome is unknown.
+    #[error("unable to monitor child: {0}")]
+    Monitor(String),
+}
+
+#[cfg(any(test, feature = "fork"))]
+fn format_stderr(stderr: &str) -> String {
+    if stderr.is_empty() {
+        String::new()
+    } else {
+        format!("; stderr:\n{}", stderr)
+    }
+}
+
+mod builder;
+
+pub use builder::{ReceiverBuilder, SharedRingBufferBuilder};
+
+mod sync_receiver;
+
+pub use sync_receiver::SyncReceiver;
+
+mod select;
+
+pub use select::Selector;
+
+mod schema;
+
+pub use schema::{schema_hash, Schema};
+
+mod tagged;
+
+pub use tagged::{Tagged, TaggedMessage};
+
+mod spsc;
+
+pub use spsc::SpscSender;
+
+mod broadcast;
+
+pub use broadcast::BroadcastReceiver;
+
+mod config;
+
+pub use config::{Config, Endian, IntEncoding};
+
+mod rpc;
+
+pub use rpc::{Client, Server};
+
+#[cfg(feature = "async")]
+mod async_channel;
+
+#[cfg(feature = "async")]
+pub use async_channel::{AsyncReceiver, AsyncSender};
+
+#[cfg(all(feature = "async", feature = "fork"))]
+mod async_fork;
+
+#[cfg(feature = "compression")]
+mod compression;
+
+#[cfg(feature = "compression")]
+pub use compression::Compression;
+
+#[cfg(all(feature = "async", feature = "fork"))]
+pub use async_fork::{fork_async, ChildFuture};
+
 /// Crate version (e.g. for logging at runtime)
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -51,9 +197,114 @@ pub const GIT_COMMIT_SHA_SHORT: Option<&str> = option_env!("VERGEN_GIT_SHA_SHORT
 /// Offset into shared memory file to find beginning of ring buffer data.
 const BEGINNING: u32 = mem::size_of::<Header>() as u32;
 
+/// Maximum number of [`BroadcastReceiver`](struct.BroadcastReceiver.html)s which may be registered with a ring
+/// buffer at once, since each needs a slot in the header for its read position.
+pub const MAX_BROADCAST_RECEIVERS: usize = 8;
+
+/// Size in bytes of the header at the start of each shared memory file, preceding the ring buffer data.
+///
+/// This varies by platform but is fixed for a given build of this crate.  The total size of the file backing a
+/// ring buffer with capacity `size_in_bytes` is `HEADER_SIZE + size_in_bytes + MESSAGE_OVERHEAD + 4`, the last four
+/// bytes being reserved so the read and write pointers never coincide unless the ring buffer is empty.
+pub const HEADER_SIZE: u32 = BEGINNING;
+
+/// Number of bytes of framing written to the ring buffer alongside each message, in addition to its serialized
+/// size.  Zero-sized messages (see
+/// [`SharedRingBufferBuilder::allow_zero_sized`](struct.SharedRingBufferBuilder.html#method.allow_zero_sized))
+/// are the exception, occupying only four bytes.
+///
+/// The capacity passed to [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create) (i.e. its
+/// `size_in_bytes`) is the size of the largest single message the ring buffer can hold, since this overhead is
+/// accounted for separately.  More generally, a newly created ring buffer of capacity `size_in_bytes` can hold up
+/// to `1 + (size_in_bytes - n) / (n + MESSAGE_OVERHEAD)` messages of `n <= size_in_bytes` serialized bytes each
+/// before a sender must wait, e.g. when using
+/// [`Sender::send_all_or_none`](struct.Sender.html#method.send_all_or_none).  Fewer may fit once the read and write
+/// pointers have advanced, since each message must be stored contiguously.  Even so, a single message of up to
+/// `size_in_bytes` is never rejected, whatever the positions of the pointers: if necessary, the sender waits for the
+/// receiver to drain the ring buffer and then writes the message at the beginning.
+pub const MESSAGE_OVERHEAD: u32 = 4 + FINGERPRINT_SIZE + COMPRESSION_SIZE + CHECKSUM_SIZE;
+
+/// Version of the shared memory layout (i.e. the `Header` and message framing) written by this version of
+/// `ipmpsc`, stored in the upper bits of the header flags.
+///
+/// This must be incremented whenever the layout changes.  Ring buffers created by versions of `ipmpsc` which
+/// predate this field have layout version zero.
+const LAYOUT_VERSION: u32 = 18;
+
+/// Number of bits the layout version is shifted left by within the header flags
+const LAYOUT_VERSION_SHIFT: u32 = 16;
+
 /// If set, indicates the ring buffer was created by a 64-bit process (32-bit otherwise)
 const FLAG_64_BIT: u32 = 1;
 
+/// If set, indicates each message in the ring buffer is prefixed with a configuration fingerprint byte
+const FLAG_FINGERPRINT: u32 = 2;
+
+/// If set, indicates receivers wait for messages using a futex rather than the condition variable
+const FLAG_FUTEX: u32 = 4;
+
+/// If set, indicates each message in the ring buffer is prefixed with a CRC32 checksum of its serialized bytes
+const FLAG_CHECKSUM: u32 = 8;
+
+/// If set, indicates senders wake a `PollableReceiver` (if armed) after publishing a message
+const FLAG_POLLABLE: u32 = 16;
+
+/// If set, indicates each message in the ring buffer is prefixed with a byte indicating how it was compressed
+const FLAG_COMPRESSION: u32 = 32;
+
+/// Per-buffer option (stored in `Header::options`) indicating that senders may send zero-sized messages
+const OPTION_ZERO_SIZED: u32 = 1;
+
+/// Per-buffer option (stored in `Header::options`) indicating that the only sender is an `SpscSender`
+const OPTION_SINGLE_PRODUCER: u32 = 2;
+
+/// Per-buffer option (stored in `Header::options`) indicating that the receiver should wake only one waiting sender
+/// when freeing space, unless the ring buffer has become empty
+const OPTION_WAKE_ONE: u32 = 4;
+
+/// Value stored in the last field of the header (`Header::canary`) so that, together with the flags in the first
+/// field, a stray write clobbering the header (e.g. a buggy process writing at offset zero) can be detected.
+const HEADER_CANARY: u64 = 0x6970_6d70_7363_2121;
+
+/// Size word indicating a zero-sized message, which occupies only the size word itself.
+///
+/// A size word of zero can't be used for this since it marks the point where the writer wrapped around to the
+/// beginning of the ring buffer.
+const EMPTY_MESSAGE: u32 = u32::MAX;
+
+/// Fingerprint of the default bincode configuration, written ahead of each message and checked by the receiver.
+///
+/// The high nibble is a fixed marker (`0b1010`) making it unlikely that an arbitrary byte will be mistaken for a
+/// fingerprint.  Bit 0 is set for big-endian encoding and bit 1 for variable-length integer encoding (see
+/// `Config::fingerprint`); neither is set here since `bincode::serialize` uses little-endian, fixed-length integers.
+const CONFIG_FINGERPRINT: u8 = 0b1010_0000;
+
+/// Size of the configuration fingerprint written ahead of each message, if enabled.
+#[cfg(feature = "fingerprint")]
+const FINGERPRINT_SIZE: u32 = 1;
+
+#[cfg(not(feature = "fingerprint"))]
+const FINGERPRINT_SIZE: u32 = 0;
+
+/// Size of the compression marker written ahead of each message (after the configuration fingerprint, if any), if
+/// enabled.
+#[cfg(feature = "compression")]
+const COMPRESSION_SIZE: u32 = 1;
+
+#[cfg(not(feature = "compression"))]
+const COMPRESSION_SIZE: u32 = 0;
+
+/// Compression marker for a message stored as serialized, which is the only kind without the `compression` feature.
+const UNCOMPRESSED: u8 = 0;
+
+/// Size of the checksum written ahead of each message (after the configuration fingerprint and compression marker,
+/// if any), if enabled.
+#[cfg(feature = "checksum")]
+const CHECKSUM_SIZE: u32 = 4;
+
+#[cfg(not(feature = "checksum"))]
+const CHECKSUM_SIZE: u32 = 0;
+
 /// `ipmpsc`-specific error type
 #[derive(ThisError, Debug)]
 pub enum Error {
@@ -63,7 +314,8 @@ pub enum Error {
     AlreadyReceived,
 
     /// Error indicating that the caller attempted to send a message of zero serialized size, which is not
-    /// supported.
+    /// supported unless enabled using
+    /// [`SharedRingBufferBuilder::allow_zero_sized`](struct.SharedRingBufferBuilder.html#method.allow_zero_sized).
     #[error("Serialized size of message is zero")]
     ZeroSizedMessage,
 
@@ -72,15 +324,153 @@ pub enum Error {
     #[error("Serialized size of message is too large for ring buffer")]
     MessageTooLarge,
 
+    /// Error indicating that the caller attempted to create a ring buffer too small to hold even a single message
+    /// (i.e. of size zero).
+    #[error("Ring buffer size must be at least one byte")]
+    BufferTooSmall,
+
     /// Error indicating the the maximum number of simultaneous senders has been exceeded.
     #[error("Too many simultaneous senders")]
     TooManySenders,
 
+    /// Error indicating that [`MAX_BROADCAST_RECEIVERS`](constant.MAX_BROADCAST_RECEIVERS.html)
+    /// [`BroadcastReceiver`](struct.BroadcastReceiver.html)s are already registered with the ring buffer.
+    #[error("Too many simultaneous broadcast receivers")]
+    TooManyReceivers,
+
+    /// Error indicating that the ring buffer is empty and every [`Sender`](struct.Sender.html) which was ever
+    /// connected to it has been dropped, so no more messages will arrive.  Copies of senders inherited via `fork`
+    /// don't count; see [`Sender`](struct.Sender.html).
+    #[error("All senders have disconnected")]
+    NoSenders,
+
     /// Error indicating the ring buffer was initialized by an incompatible version of `ipmpsc` and/or by a process
-    /// with a different word size (32-bit vs. 64-bit)
+    /// with a different word size (32-bit vs. 64-bit), or that the file is not an `ipmpsc` ring buffer at all
     #[error("Incompatible ring buffer (e.g. 32-bit vs. 64-bit or wrong ipmpsc version)")]
     IncompatibleRingBuffer,
 
+    /// Error indicating that the file passed to
+    /// [`SharedRingBuffer::open`](struct.SharedRingBuffer.html#method.open) (or similar) has not been initialized
+    /// as a ring buffer, e.g. because it is empty or was never passed to
+    /// [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create).
+    #[error("File has not been initialized as a ring buffer")]
+    Uninitialized,
+
+    /// Error indicating the ring buffer was initialized by an older version of `ipmpsc` which used a different
+    /// shared memory layout.  It must be recreated (e.g. using
+    /// [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create)) before it can be used.
+    #[error("Ring buffer layout version {found} is too old (minimum supported version is {min})")]
+    VersionTooOld {
+        /// Layout version found in the ring buffer
+        found: u32,
+        /// Oldest layout version supported by this version of `ipmpsc`
+        min: u32,
+    },
+
+    /// Error indicating the ring buffer was initialized by a semver-incompatible version of `ipmpsc` (i.e. one
+    /// with a different major version, or a different minor version prior to 1.0), which may frame or encode
+    /// messages differently even if the shared memory layout happens to match.
+    #[error("Ring buffer created by ipmpsc {found}, which is incompatible with this version ({expected})")]
+    VersionMismatch {
+        /// Compatible version range of the `ipmpsc` which created the ring buffer (e.g. "0.5" or "1")
+        found: String,
+        /// Compatible version range of this version of `ipmpsc`
+        expected: String,
+    },
+
+    /// Error indicating that the ring buffer header (which holds the lock and the read and write pointers) has
+    /// been overwritten, e.g. by a buggy process writing to the shared memory file directly.
+    #[error("Corrupt ring buffer header")]
+    CorruptHeader,
+
+    /// Error indicating that the ring buffer's read or write pointer refers to a location outside the data region,
+    /// e.g. due to corruption of the shared memory file.
+    #[error("Corrupt ring buffer")]
+    CorruptBuffer,
+
+    /// Error indicating that a message was encoded using a different bincode configuration (e.g. endianness or
+    /// integer encoding) than the receiver expects.  Only reported when the `fingerprint` feature is enabled.
+    #[error("Message encoded with mismatched bincode configuration (fingerprint {found:#04x}, expected {expected:#04x})")]
+    ConfigMismatch {
+        /// Fingerprint found in the message
+        found: u8,
+        /// Fingerprint expected by the receiver
+        expected: u8,
+    },
+
+    /// Error indicating that the serialized bytes of a message don't match the checksum the sender computed for them,
+    /// e.g. because a buggy process wrote past the end of its own message.  Only reported when the `checksum`
+    /// feature is enabled.  The corrupt message is discarded, so subsequent receives may proceed.
+    #[error("Message checksum mismatch (found {found:#010x}, expected {expected:#010x})")]
+    ChecksumMismatch {
+        /// Checksum computed from the message as received
+        found: u32,
+        /// Checksum the sender stored alongside the message
+        expected: u32,
+    },
+
+    /// Error indicating that a compressed message (see
+    /// [`Sender::with_compression`](struct.Sender.html#method.with_compression)) was received using a zero-copy
+    /// method such as [`Receiver::recv_ref`](struct.Receiver.html#method.recv_ref), which can't borrow it from the
+    /// ring buffer since it must be decompressed first.  It may be received using e.g.
+    /// [`Receiver::recv`](struct.Receiver.html#method.recv) instead.
+    #[error("Compressed message can't be received without copying")]
+    Compressed,
+
+    /// Error indicating that a [`Sender`](struct.Sender.html) was used with a ring buffer created for a single
+    /// producer (see
+    /// [`SharedRingBufferBuilder::single_producer`](struct.SharedRingBufferBuilder.html#method.single_producer)), or
+    /// that an [`SpscSender`](struct.SpscSender.html) was constructed for one which was not.
+    #[error("Wrong kind of sender for ring buffer (Sender vs. SpscSender)")]
+    WrongSenderKind,
+
+    /// Error indicating that a message received using
+    /// [`Receiver::recv_checked`](struct.Receiver.html#method.recv_checked) (or similar) was sent with a different
+    /// [`Schema::SCHEMA_HASH`](trait.Schema.html#associatedconstant.SCHEMA_HASH) than the receiver expects, e.g.
+    /// because the sender was built against a different version of the message type.
+    #[error("Message sent with mismatched schema (fingerprint {found:#018x}, expected {expected:#018x})")]
+    SchemaMismatch {
+        /// Fingerprint found in the message
+        found: u64,
+        /// Fingerprint expected by the receiver
+        expected: u64,
+    },
+
+    /// Error indicating that a [`TaggedMessage`](struct.TaggedMessage.html) was deserialized as a type with a
+    /// different [`Tagged::TAG`](trait.Tagged.html#associatedconstant.TAG) than it was sent with.
+    #[error("Message has tag {found}, expected {expected}")]
+    TagMismatch {
+        /// Tag found in the message
+        found: u16,
+        /// Tag of the type requested by the receiver
+        expected: u16,
+    },
+
+    /// Error indicating that a send was interrupted using
+    /// [`ShutdownToken::shutdown`](struct.ShutdownToken.html#method.shutdown) rather than waiting for space in the
+    /// ring buffer, or that a receive found the ring buffer empty after it was shut down, so no more messages will
+    /// arrive.
+    #[error("Interrupted by shutdown")]
+    Interrupted,
+
+    /// Error indicating that the ring buffer was resized using
+    /// [`Receiver::resize`](struct.Receiver.html#method.resize) after this handle to it was opened, so it must be
+    /// opened again (e.g. using [`SharedRingBuffer::open`](struct.SharedRingBuffer.html#method.open)) to continue
+    /// sending.
+    #[error("Ring buffer was resized; open it again to continue")]
+    Resized,
+
+    /// Error indicating that the [`Receiver`](struct.Receiver.html) for a ring buffer was dropped (e.g. because the
+    /// process which owned it exited) while waiting for it to respond, as reported by
+    /// [`Client::call`](struct.Client.html#method.call).
+    #[error("Receiver dropped while waiting for a reply")]
+    NoReceiver,
+
+    /// Error indicating that a send with a timeout could not acquire the ring buffer lock in time, e.g. because
+    /// another sender held it for too long.
+    #[error("Timed out waiting for ring buffer lock")]
+    LockTimeout,
+
     /// Implementation-specific runtime failure (e.g. a libc mutex error).
     #[error("{0}")]
     Runtime(String),
@@ -97,20 +487,399 @@ pub enum Error {
 /// `ipmpsc`-specific Result type alias
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Describes how a call to [`Sender::send_reporting`](struct.Sender.html#method.send_reporting) completed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SendOutcome {
+    /// The message was written without waiting for space to become available in the ring buffer.
+    Immediate,
+
+    /// The sender had to wait for space to become available in the ring buffer before writing the message.
+    Blocked {
+        /// How long the sender spent waiting for space
+        waited: Duration,
+    },
+}
+
+/// Cumulative statistics for a ring buffer, as returned by
+/// [`SharedRingBuffer::stats`](struct.SharedRingBuffer.html#method.stats), e.g. for capacity planning.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Stats {
+    /// Number of messages sent by all senders
+    pub messages_sent: u64,
+
+    /// Number of bytes those messages occupied in the ring buffer, including framing (see
+    /// [`MESSAGE_OVERHEAD`](constant.MESSAGE_OVERHEAD.html))
+    pub bytes_sent: u64,
+
+    /// Number of times a sender found too little space at the end of the ring buffer for a message and wrapped
+    /// around to the beginning
+    ///
+    /// Frequent wrapping means senders regularly fill the ring buffer to near the end before the receiver catches
+    /// up, which suggests it is undersized.
+    pub wrap_count: u64,
+}
+
+/// Describes what a call to
+/// [`SharedRingBuffer::create_reporting`](struct.SharedRingBuffer.html#method.create_reporting) found at the
+/// specified path.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CreateOutcome {
+    /// No file existed, so a new one was created.
+    Created,
+
+    /// A file already existed and was truncated, discarding its contents (e.g. another component's ring buffer).
+    Truncated,
+}
+
+/// Selects the kind of mutex used to synchronize access to a ring buffer's header, trading off speed against
+/// resilience.
+///
+/// This only affects POSIX platforms; it is ignored on Windows.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub enum MutexKind {
+    /// The platform's default mutex type
+    #[default]
+    Default,
+
+    /// A `PTHREAD_MUTEX_NORMAL` mutex, which does no error checking and is typically the fastest option
+    Normal,
+
+    /// A `PTHREAD_MUTEX_ADAPTIVE_NP` mutex, which spins briefly before sleeping, potentially reducing latency under
+    /// low contention (Linux with glibc only)
+    Adaptive,
+
+    /// A robust mutex, which remains usable if a process dies while holding it rather than deadlocking every other
+    /// process using the ring buffer (Linux only)
+    ///
+    /// No recovery is needed beyond marking the mutex consistent: a sender only publishes a new write pointer once
+    /// the message preceding it is complete, so if it dies partway through writing one, the partial message is
+    /// simply overwritten by the next sender.
+    Robust,
+}
+
+/// Returns the semver-compatible part of this crate's version (i.e. the major version, plus the minor version prior
+/// to 1.0), encoded as `major << 16 | minor`.
+fn crate_version() -> u32 {
+    let major = env!("CARGO_PKG_VERSION_MAJOR").parse::<u32>().unwrap_or(0);
+    let minor = if major == 0 {
+        env!("CARGO_PKG_VERSION_MINOR").parse::<u32>().unwrap_or(0)
+    } else {
+        0
+    };
+
+    (major << 16) | minor
+}
+
+/// Format a version encoded by `crate_version` for display.
+fn format_crate_version(version: u32) -> String {
+    match version >> 16 {
+        0 => format!("0.{}", version & 0xFFFF),
+        major => major.to_string(),
+    }
+}
+
 fn flags() -> u32 {
-    if mem::size_of::<*const c_void>() == 8 {
+    let word_size = if mem::size_of::<*const c_void>() == 8 {
         FLAG_64_BIT
     } else {
         0
+    };
+
+    let fingerprint = if cfg!(feature = "fingerprint") {
+        FLAG_FINGERPRINT
+    } else {
+        0
+    };
+
+    let futex = if cfg!(all(feature = "futex", target_os = "linux")) {
+        FLAG_FUTEX
+    } else {
+        0
+    };
+
+    let checksum = if cfg!(feature = "checksum") {
+        FLAG_CHECKSUM
+    } else {
+        0
+    };
+
+    let pollable = if cfg!(all(feature = "pollable", target_os = "linux")) {
+        FLAG_POLLABLE
+    } else {
+        0
+    };
+
+    let compression = if cfg!(feature = "compression") {
+        FLAG_COMPRESSION
+    } else {
+        0
+    };
+
+    (LAYOUT_VERSION << LAYOUT_VERSION_SHIFT)
+        | word_size
+        | fingerprint
+        | futex
+        | checksum
+        | pollable
+        | compression
+}
+
+/// Wake any receiver waiting for a message using a futex or a `PollableReceiver`, if enabled.
+///
+/// This must be called after publishing new messages or disconnecting a sender.  It is in addition to (rather than
+/// instead of) notifying the condition variable, which is still used by senders waiting for space and by
+/// `AsyncReceiver`.
+#[allow(unused_variables)]
+fn notify_data_ready(buffer: &Buffer) {
+    #[cfg(all(feature = "futex", target_os = "linux"))]
+    {
+        let header = buffer.header();
+        header.notify_seq.fetch_add(1, Release);
+        futex::wake_all(&header.notify_seq);
+    }
+
+    #[cfg(all(feature = "pollable", target_os = "linux"))]
+    pollable::notify(buffer.header());
+}
+
+/// Returns `true` if the receiver is busy-waiting for messages (see `Receiver::spin`) and nothing is blocked
+/// waiting on the condition variable for them, in which case a sender may skip waking anyone after publishing a new
+/// write pointer.
+///
+/// The sender must store the write pointer with `SeqCst` ordering before calling this.  The receiver clears
+/// `Header::receiver_spinning` (also with `SeqCst`) before re-checking the write pointer and blocking, so either we
+/// see the flag cleared or it sees the new write pointer.
+fn receiver_spinning(header: &Header) -> bool {
+    header.receiver_spinning.load(SeqCst) != 0 && header.waiting_receivers.load(SeqCst) == 0
+}
+
+/// Record the current time in `heartbeat`.
+fn stamp(heartbeat: &AtomicU64) {
+    // Zero means "never stamped", so make sure we never store that.
+    heartbeat.store(os::monotonic_millis().max(1), Release);
+}
+
+/// Returns `true` if `heartbeat` has never been stamped or was last stamped more than `max_age` ago.
+fn stale(heartbeat: &AtomicU64, max_age: Duration) -> bool {
+    match heartbeat.load(Acquire) {
+        0 => true,
+        last => u128::from(os::monotonic_millis().saturating_sub(last)) > max_age.as_millis(),
+    }
+}
+
+/// Verify that the configuration fingerprint at the beginning of `frame` (if enabled) matches `expected`, returning
+/// the remainder.
+#[cfg(feature = "fingerprint")]
+fn check_fingerprint(frame: &[u8], expected: u8) -> Result<&[u8]> {
+    match frame.split_first() {
+        Some((&found, body)) if found == expected => Ok(body),
+        Some((&found, _)) => Err(Error::ConfigMismatch { found, expected }),
+        None => Err(Error::CorruptBuffer),
+    }
+}
+
+#[cfg(not(feature = "fingerprint"))]
+fn check_fingerprint(frame: &[u8], _expected: u8) -> Result<&[u8]> {
+    Ok(frame)
+}
+
+/// Verify that the checksum at the beginning of `frame` (if enabled) matches the remainder, returning the
+/// remainder.
+#[cfg(feature = "checksum")]
+fn check_checksum(frame: &[u8]) -> Result<&[u8]> {
+    if frame.len() < CHECKSUM_SIZE as usize {
+        return Err(Error::CorruptBuffer);
+    }
+
+    let (checksum, body) = frame.split_at(CHECKSUM_SIZE as usize);
+    let expected = bincode::deserialize::<u32>(checksum)?;
+    let found = crc32fast::hash(body);
+
+    if found == expected {
+        Ok(body)
+    } else {
+        Err(Error::ChecksumMismatch { found, expected })
+    }
+}
+
+#[cfg(not(feature = "checksum"))]
+fn check_checksum(frame: &[u8]) -> Result<&[u8]> {
+    Ok(frame)
+}
+
+/// Read the compression marker at the beginning of `frame` (if enabled), returning it along with the remainder.
+#[cfg(feature = "compression")]
+fn check_compression(frame: &[u8]) -> Result<(u8, &[u8])> {
+    match frame.split_first() {
+        Some((&marker, rest)) => Ok((marker, rest)),
+        None => Err(Error::CorruptBuffer),
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+fn check_compression(frame: &[u8]) -> Result<(u8, &[u8])> {
+    Ok((UNCOMPRESSED, frame))
+}
+
+#[cfg(feature = "compression")]
+use compression::decompress;
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_marker: u8, message: &[u8]) -> Result<Cow<'_, [u8]>> {
+    Ok(Cow::Borrowed(message))
+}
+
+/// Verify the configuration fingerprint (against `fingerprint`) and checksum (if enabled) at the beginning of
+/// `frame`, returning the compression marker and the message as stored.
+fn check_frame_stored(frame: &[u8], fingerprint: u8) -> Result<(u8, &[u8])> {
+    let (marker, frame) = check_compression(check_fingerprint(frame, fingerprint)?)?;
+    Ok((marker, check_checksum(frame)?))
+}
+
+/// Verify the configuration fingerprint and checksum (if enabled) at the beginning of `frame`, returning the
+/// serialized message which follows them, decompressed if necessary.
+fn check_frame(frame: &[u8], fingerprint: u8) -> Result<Cow<'_, [u8]>> {
+    let (marker, message) = check_frame_stored(frame, fingerprint)?;
+    decompress(marker, message)
+}
+
+/// Returns the `start..end` range of the ring buffer, verifying that it lies entirely within the data region.
+///
+/// Any accounting bug in the read/write pointer arithmetic will trip the debug assertion in debug builds and
+/// produce [`Error::CorruptBuffer`](enum.Error.html#variant.CorruptBuffer) in release builds rather than scribbling
+/// over the header or beyond the end of the mapping.
+fn region(map: &mut [u8], start: u32, end: u32) -> Result<&mut [u8]> {
+    let valid = BEGINNING <= start && start <= end && end as usize <= map.len();
+
+    debug_assert!(
+        valid,
+        "ring buffer region {}..{} out of bounds (data region is {}..{})",
+        start,
+        end,
+        BEGINNING,
+        map.len()
+    );
+
+    if valid {
+        Ok(&mut map[start as usize..end as usize])
+    } else {
+        Err(Error::CorruptBuffer)
     }
 }
 
-fn map(file: &File) -> Result<MmapMut> {
+/// Verify that the values at the beginning and end of the header (i.e. the flags and canary) are intact, returning
+/// `Err(Error::CorruptHeader)` if not.
+fn check_header(header: &Header) -> Result<()> {
+    if header.flags.load(Relaxed) == flags() && header.canary.load(Relaxed) == HEADER_CANARY {
+        Ok(())
+    } else {
+        Err(Error::CorruptHeader)
+    }
+}
+
+/// Checks invariants relating the read and write pointers to each other and to the size of the mapping, catching
+/// accounting bugs as soon as they occur in debug builds (e.g. while running tests).  This compiles to nothing in
+/// release builds.
+fn debug_check_pointers(read: u32, write: u32, map_len: usize) {
+    let (read, write, map_len) = (u64::from(read), u64::from(write), map_len as u64);
+    let beginning = u64::from(BEGINNING);
+
+    debug_assert!(
+        beginning <= read && read <= map_len,
+        "read pointer {} out of bounds (data region is {}..{})",
+        read,
+        beginning,
+        map_len
+    );
+
+    // Senders always leave room for a wrap marker after the last frame:
+    debug_assert!(
+        beginning <= write && write + 4 <= map_len,
+        "write pointer {} out of bounds (data region is {}..{})",
+        write,
+        beginning,
+        map_len
+    );
+
+    // The number of occupied bytes (counting any space skipped by a wrap marker as occupied) can't exceed the
+    // size of the data region:
+    let occupied = if write >= read {
+        write - read
+    } else {
+        (map_len - read) + (write - beginning)
+    };
+
+    debug_assert!(
+        occupied <= map_len - beginning,
+        "occupied size {} exceeds capacity {} (read: {}, write: {})",
+        occupied,
+        map_len - beginning,
+        read,
+        write
+    );
+}
+
+/// Returns the number of bytes between the `read` and `write` pointers, counting any space skipped by a wrap marker
+/// as occupied.
+fn occupied(read: u32, write: u32, map_len: usize) -> u32 {
+    if write >= read {
+        write - read
+    } else {
+        (map_len as u32 - read) + (write - BEGINNING)
+    }
+}
+
+/// Computes the length of the shared memory file needed for a ring buffer which can hold messages of up to
+/// `size_in_bytes` serialized bytes.
+fn file_len(size_in_bytes: u32) -> Result<u64> {
+    if size_in_bytes == 0 {
+        Err(Error::BufferTooSmall)
+    } else {
+        Ok(u64::from(BEGINNING) + u64::from(size_in_bytes) + u64::from(MESSAGE_OVERHEAD) + 4)
+    }
+}
+
+/// Generates a random identifier for a newly created ring buffer.
+///
+/// This need not be cryptographically secure, so we avoid a dependency on `rand` by hashing the current time and
+/// process ID with the randomly-seeded hasher used by `HashMap`.
+fn random_id() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    if let Ok(elapsed) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+    hasher.finish()
+}
+
+/// Creates a temporary file in `dir` (or the default temporary directory, e.g. `$TMPDIR`, if `None`) whose name
+/// starts with `prefix` (if any), which will be deleted when dropped.
+///
+/// Every temporary ring buffer is created using this function, so it is the one place to look when temporary files
+/// end up somewhere unexpected.
+fn temp_file(dir: Option<&Path>, prefix: Option<&str>) -> Result<NamedTempFile> {
+    let mut builder = tempfile::Builder::new();
+    if let Some(prefix) = prefix {
+        builder.prefix(prefix);
+    }
+
+    Ok(if let Some(dir) = dir {
+        builder.tempfile_in(dir)?
+    } else {
+        builder.tempfile()?
+    })
+}
+
+fn map(file: &File, options: &SharedRingBufferBuilder) -> Result<MmapMut> {
     unsafe {
         let map = MmapMut::map_mut(file)?;
 
         #[allow(clippy::cast_ptr_alignment)]
-        (*(map.as_ptr() as *const Header)).init()?;
+        (*(map.as_ptr() as *const Header)).init(
+            options.mutex_kind,
+            options.option_bits(),
+            options.max_messages,
+        )?;
 
         Ok(map)
     }
@@ -134,35 +903,160 @@ impl SharedRingBuffer {
     ///
     /// The file will be created if it does not already exist or truncated otherwise.
     ///
+    /// `size_in_bytes` is the size of the largest message (in serialized bytes) the ring buffer can hold, not
+    /// counting per-message overhead, which is accounted for separately.  For example, a `u8` message serializes
+    /// to a single byte, so `size_in_bytes` of 1 is the smallest useful size.  A size of zero will result in
+    /// `Err(`[`Error::BufferTooSmall`](enum.Error.html#variant.BufferTooSmall)`)`.
+    ///
     /// Once this function completes successfully, the same path may be used to create one or more corresponding
     /// instances in other processes using the [`SharedRingBuffer::open`](struct.SharedRingBuffer.html#method.open)
     /// method.
+    ///
+    /// See [`SharedRingBufferBuilder`](struct.SharedRingBufferBuilder.html) for additional options.
     pub fn create(path: &str, size_in_bytes: u32) -> Result<Self> {
-        let file = OpenOptions::new()
+        SharedRingBufferBuilder::new(size_in_bytes).create(path)
+    }
+
+    /// Creates a new [`SharedRingBuffer`](struct.SharedRingBuffer.html) exactly like
+    /// [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create), but also reports whether a file
+    /// already existed at the specified path and was truncated.
+    ///
+    /// This allows a component to detect (and e.g. warn about) unexpectedly wiping out a ring buffer created by
+    /// someone else.  See [`SharedRingBuffer::create_or_resume`](struct.SharedRingBuffer.html#method.create_or_resume)
+    /// for a non-destructive alternative.
+    pub fn create_reporting(path: &str, size_in_bytes: u32) -> Result<(Self, CreateOutcome)> {
+        SharedRingBufferBuilder::new(size_in_bytes).create_reporting(path)
+    }
+
+    /// Creates a new [`SharedRingBuffer`](struct.SharedRingBuffer.html) backed by a file with the specified name,
+    /// sized to hold `max_messages` messages of up to `max_message_size` serialized bytes each, and allowing no more
+    /// than `max_messages` to be queued at once.
+    ///
+    /// Senders wait (or, with [`Sender::try_send`](struct.Sender.html#method.try_send), return `Ok(false)`) once
+    /// `max_messages` are queued, which gives more predictable backpressure than waiting for bytes to run out.
+    /// The ring buffer capacity is chosen so that, regardless of where the read and write pointers are, space
+    /// never runs out before the message limit is reached, provided no message exceeds `max_message_size`.
+    ///
+    /// This is equivalent to using
+    /// [`SharedRingBufferBuilder::max_messages`](struct.SharedRingBufferBuilder.html#method.max_messages) with a
+    /// suitable capacity.  A `max_messages` of zero will result in
+    /// `Err(`[`Error::BufferTooSmall`](enum.Error.html#variant.BufferTooSmall)`)`, and a combination too large to
+    /// represent will result in `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`)`.
+    pub fn create_bounded(path: &str, max_messages: u32, max_message_size: u32) -> Result<Self> {
+        if max_messages == 0 {
+            return Err(Error::BufferTooSmall);
+        }
+
+        // Each message must be stored contiguously, so up to one frame's worth of space may be wasted at the end
+        // of the ring buffer when wrapping; reserving room for an extra frame covers that.
+        let size_in_bytes = frame_size(max_message_size)
+            .and_then(|frame_size| frame_size.checked_mul(max_messages.checked_add(1)?))
+            .and_then(|size| size.checked_add(4))
+            .ok_or(Error::MessageTooLarge)?;
+
+        SharedRingBufferBuilder::new(size_in_bytes)
+            .max_messages(max_messages)
+            .create(path)
+    }
+
+    /// Attaches to an existing ring buffer backed by a file with the specified name, preserving any unreceived
+    /// messages, or creates a new one as with [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create)
+    /// if the file does not exist or does not contain a compatible ring buffer with the specified capacity.
+    ///
+    /// This is intended for ring buffers backed by files on a persistent filesystem which are used as durable queues
+    /// (see [`SharedRingBuffer::flush_to_disk`](struct.SharedRingBuffer.html#method.flush_to_disk)), allowing a
+    /// process to pick up where a previous one left off.  Note that the existing ring buffer's state is used as is,
+    /// so any senders in processes which exited without running destructors will still be counted as connected.
+    pub fn create_or_resume(path: &str, size_in_bytes: u32) -> Result<Self> {
+        SharedRingBufferBuilder::new(size_in_bytes).create_or_resume(path)
+    }
+
+    fn create_or_resume_0(path: &str, options: &SharedRingBufferBuilder) -> Result<Self> {
+        match Self::open(path) {
+            Ok(buffer) if buffer.size_in_bytes() == options.size_in_bytes => Ok(buffer),
+            Ok(_)
+            | Err(Error::IncompatibleRingBuffer)
+            | Err(Error::Uninitialized)
+            | Err(Error::VersionTooOld { .. })
+            | Err(Error::VersionMismatch { .. })
+            | Err(Error::CorruptBuffer) => Ok(Self::create_0(path, options)?.0),
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+                Ok(Self::create_0(path, options)?.0)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn create_0(path: &str, options: &SharedRingBufferBuilder) -> Result<(Self, CreateOutcome)> {
+        let (file, outcome) = match OpenOptions::new()
             .read(true)
             .write(true)
-            .create(true)
-            .truncate(true)
-            .open(path)?;
+            .create_new(true)
+            .open(path)
+        {
+            Ok(file) => (file, CreateOutcome::Created),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => (
+                OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .truncate(true)
+                    .open(path)?,
+                CreateOutcome::Truncated,
+            ),
+            Err(e) => return Err(e.into()),
+        };
 
-        file.set_len(u64::from(BEGINNING + size_in_bytes + 8))?;
+        file.set_len(file_len(options.size_in_bytes)?)?;
 
-        Ok(Self(View::try_new(Arc::new(UnsafeCell::new(
-            Buffer::try_new(path, map(&file)?, None)?,
-        )))?))
+        options.set_permissions(&file)?;
+
+        Ok((
+            Self(View::try_new(Arc::new(UnsafeCell::new(Buffer::try_new(
+                path,
+                map(&file, options)?,
+                None,
+            )?)))?),
+            outcome,
+        ))
     }
 
     /// Creates a new [`SharedRingBuffer`](struct.SharedRingBuffer.html) backed by a temporary file which will be
     /// deleted when the [`SharedRingBuffer`](struct.SharedRingBuffer.html) is dropped.
     ///
+    /// See [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create) for the meaning of
+    /// `size_in_bytes`.
+    ///
     /// The name of the file is returned along with the [`SharedRingBuffer`](struct.SharedRingBuffer.html) and may
     /// be used to create one or more corresponding instances in other processes using the
     /// [`SharedRingBuffer::open`](struct.SharedRingBuffer.html#method.open) method.
+    ///
+    /// See [`SharedRingBufferBuilder`](struct.SharedRingBufferBuilder.html) for additional options.
     pub fn create_temp(size_in_bytes: u32) -> Result<(String, Self)> {
-        let file = NamedTempFile::new()?;
+        SharedRingBufferBuilder::new(size_in_bytes).create_temp()
+    }
 
-        file.as_file()
-            .set_len(u64::from(BEGINNING + size_in_bytes + 8))?;
+    /// Creates a new [`SharedRingBuffer`](struct.SharedRingBuffer.html) backed by a temporary file whose name starts
+    /// with `prefix`, which will be deleted when the [`SharedRingBuffer`](struct.SharedRingBuffer.html) is dropped.
+    ///
+    /// This is otherwise identical to
+    /// [`SharedRingBuffer::create_temp`](struct.SharedRingBuffer.html#method.create_temp).  The rest of the name is
+    /// random, so a recognizable prefix (e.g. `myapp-ipc-<pid>-`) allows other processes or tools to find the file
+    /// by listing the temporary directory, without the name having to be communicated out of band.
+    pub fn create_temp_named(prefix: &str, size_in_bytes: u32) -> Result<(String, Self)> {
+        SharedRingBufferBuilder::new(size_in_bytes).create_temp_named(prefix)
+    }
+
+    fn create_temp_0(
+        dir: Option<&Path>,
+        prefix: Option<&str>,
+        options: &SharedRingBufferBuilder,
+    ) -> Result<(String, Self)> {
+        let len = file_len(options.size_in_bytes)?;
+        let file = temp_file(dir, prefix)?;
+
+        file.as_file().set_len(len)?;
+
+        options.set_permissions(file.as_file())?;
 
         let path = file
             .path()
@@ -170,7 +1064,7 @@ impl SharedRingBuffer {
             .ok_or_else(|| Error::Runtime("unable to represent path as string".into()))?
             .to_owned();
 
-        let map = map(file.as_file())?;
+        let map = map(file.as_file(), options)?;
 
         Ok((
             path.to_owned(),
@@ -186,541 +1080,5340 @@ impl SharedRingBuffer {
     ///
     /// The file must already exist and have been initialized by a call to
     /// [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create) or
-    /// [`SharedRingBuffer::create_temp`](struct.SharedRingBuffer.html#method.create_temp).
+    /// [`SharedRingBuffer::create_temp`](struct.SharedRingBuffer.html#method.create_temp).  Its header is validated
+    /// but never reinitialized, so any unreceived messages are preserved.  If the file is empty or otherwise
+    /// uninitialized, this method will return `Err(`[`Error::Uninitialized`](enum.Error.html#variant.Uninitialized)`))`.
     pub fn open(path: &str) -> Result<Self> {
         let file = OpenOptions::new().read(true).write(true).open(path)?;
+
+        // The flags are the first field of every header layout, so they can be read regardless of version:
+        if file.metadata()?.len() < mem::size_of::<u32>() as u64 {
+            return Err(Error::Uninitialized);
+        }
+
         let map = unsafe { MmapMut::map_mut(&file)? };
 
-        let buffer = Buffer::try_new(path, map, None)?;
+        #[allow(clippy::cast_ptr_alignment)]
+        let flags = unsafe { (*(map.as_ptr() as *const AtomicU32)).load(Relaxed) };
+
+        // Every initialized header has a nonzero layout version in its flags:
+        if flags == 0 {
+            return Err(Error::Uninitialized);
+        }
+
+        let version = flags >> LAYOUT_VERSION_SHIFT;
+
+        if version < LAYOUT_VERSION {
+            return Err(Error::VersionTooOld {
+                found: version,
+                min: LAYOUT_VERSION,
+            });
+        } else if flags != crate::flags() {
+            return Err(Error::IncompatibleRingBuffer);
+        } else if map.len() < BEGINNING as usize {
+            return Err(Error::CorruptBuffer);
+        }
+
+        // The flags alone could match by coincidence, so check the canary too before trusting anything else in
+        // the header:
+        #[allow(clippy::cast_ptr_alignment)]
+        let canary = unsafe { (*(map.as_ptr() as *const Header)).canary.load(Relaxed) };
 
-        if buffer.header().flags.load(Relaxed) != crate::flags() {
+        if canary != HEADER_CANARY {
             return Err(Error::IncompatibleRingBuffer);
         }
 
+        #[allow(clippy::cast_ptr_alignment)]
+        let found = unsafe {
+            (*(map.as_ptr() as *const Header))
+                .crate_version
+                .load(Relaxed)
+        };
+
+        if found != crate_version() {
+            return Err(Error::VersionMismatch {
+                found: format_crate_version(found),
+                expected: format_crate_version(crate_version()),
+            });
+        }
+
+        let buffer = Buffer::try_new(path, map, None)?;
+
         Ok(Self(View::try_new(Arc::new(UnsafeCell::new(buffer)))?))
     }
-}
 
-/// Represents the receiving end of an inter-process channel, capable of receiving any message type implementing
-/// [`serde::Deserialize`](https://docs.serde.rs/serde/trait.Deserialize.html).
-pub struct Receiver(SharedRingBuffer);
+    /// Returns the capacity this ring buffer was created with, i.e. the `size_in_bytes` argument passed to
+    /// [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create) or
+    /// [`SharedRingBuffer::create_temp`](struct.SharedRingBuffer.html#method.create_temp).
+    pub fn size_in_bytes(&self) -> u32 {
+        (self.0.buffer().map().len() as u64 - u64::from(BEGINNING + MESSAGE_OVERHEAD + 4)) as u32
+    }
 
-impl Receiver {
-    /// Constructs a [`Receiver`](struct.Receiver.html) from the specified
-    /// [`SharedRingBuffer`](struct.SharedRingBuffer.html)
-    pub fn new(buffer: SharedRingBuffer) -> Self {
-        Self(buffer)
+    /// Returns `true` if at least one [`Sender`](struct.Sender.html) has been connected to this ring buffer and all
+    /// of them have since been dropped.
+    ///
+    /// If this returns `true`, any messages written by those senders are visible to the caller.
+    fn senders_disconnected(&self) -> bool {
+        let header = self.0.buffer().header();
+        header.sender_connected.load(Acquire) != 0 && header.sender_count.load(Acquire) == 0
     }
 
-    fn seek(&self, position: u32) -> Result<()> {
-        let buffer = self.0 .0.buffer();
-        let mut lock = buffer.lock()?;
-        buffer.header().read.store(position, Relaxed);
-        lock.notify_all()
+    /// Returns `true` if [`ShutdownToken::shutdown`](struct.ShutdownToken.html#method.shutdown) has been called for
+    /// this ring buffer.
+    fn shut_down(&self) -> bool {
+        self.0.buffer().header().shutdown.load(Acquire) != 0
     }
 
-    /// Attempt to read a message without blocking.
+    /// Read the frame at `read`, returning the message and the position of the frame following it, or `Ok(None)`
+    /// if there is none yet.
     ///
-    /// This will return `Ok(None)` if there are no messages immediately available.
-    pub fn try_recv<T>(&self) -> Result<Option<T>>
-    where
-        T: for<'de> Deserialize<'de>,
-    {
-        Ok(if let Some((value, position)) = self.try_recv_0()? {
-            self.seek(position)?;
+    /// Wrap markers and corrupt messages are skipped by calling `skip` with the position following them.
+    ///
+    /// No lock is needed, even while a sender is wrapping around: senders write each frame (including a wrap
+    /// marker's size word) in full before publishing it by storing the write pointer, and we load the write
+    /// pointer (with `Acquire`) before looking at anything it covers.  So every size word between `read` and the
+    /// write pointer we loaded is complete, and a size word of zero there always means the write pointer has
+    /// wrapped around behind `read`.  Frames published after we loaded the write pointer aren't looked at until
+    /// the next call, so a concurrent wrap can't lead us to a stale size word or a spurious
+    /// [`Error::CorruptBuffer`](enum.Error.html#variant.CorruptBuffer).
+    fn read_frame_0(
+        &self,
+        mut read: u32,
+        fingerprint: u8,
+        skip: impl Fn(u32) -> Result<()>,
+    ) -> Result<Option<(Cow<'_, [u8]>, u32)>> {
+        let buffer = self.0.buffer();
 
-            Some(value)
-        } else {
-            None
-        })
-    }
+        check_header(buffer.header())?;
 
-    fn try_recv_0<'a, T: Deserialize<'a>>(&'a self) -> Result<Option<(T, u32)>> {
-        let buffer = self.0 .0.buffer();
         let map = buffer.map();
 
-        let mut read = buffer.header().read.load(Relaxed);
+        // These must be checked before loading the write pointer so that we see every message written by senders
+        // which have disconnected or before the ring buffer was shut down.
+        let disconnected = self.senders_disconnected();
+        let shut_down = self.shut_down();
+
         let write = buffer.header().write.load(Acquire);
 
+        debug_check_pointers(read, write, map.len());
+
         Ok(loop {
             if write != read {
                 let slice = map.as_ref();
                 let start = read + 4;
                 let size = bincode::deserialize::<u32>(&slice[read as usize..start as usize])?;
-                if size > 0 {
+                if size == EMPTY_MESSAGE {
+                    break Some((Cow::Borrowed(&[][..]), start));
+                } else if size > 0 {
                     let end = start + size;
-                    break Some((
-                        bincode::deserialize(&slice[start as usize..end as usize])?,
-                        end,
-                    ));
+                    let frame = slice
+                        .get(start as usize..end as usize)
+                        .ok_or(Error::CorruptBuffer)?;
+                    match check_frame(frame, fingerprint) {
+                        Ok(message) => break Some((message, end)),
+                        Err(e @ Error::ChecksumMismatch { .. }) => {
+                            // The framing is intact, so skip the corrupt message rather than report it forever.
+                            skip(end)?;
+                            return Err(e);
+                        }
+                        Err(e) => return Err(e),
+                    }
                 } else if write < read {
                     read = BEGINNING;
-                    let mut lock = buffer.lock()?;
-                    buffer.header().read.store(read, Relaxed);
-                    lock.notify_all()?;
+                    skip(read)?;
                 } else {
-                    return Err(Error::Runtime("corrupt ring buffer".into()));
+                    return Err(Error::CorruptBuffer);
                 }
+            } else if disconnected {
+                return Err(Error::NoSenders);
+            } else if shut_down {
+                return Err(Error::Interrupted);
             } else {
                 break None;
             }
         })
     }
 
-    /// Attempt to read a message, blocking if necessary until one becomes available.
-    pub fn recv<T>(&self) -> Result<T>
-    where
-        T: for<'de> Deserialize<'de>,
-    {
-        let (value, position) = self.recv_timeout_0(None)?.unwrap();
+    /// Wait until a message is written at `read`, all senders have disconnected, the ring buffer is shut down, or
+    /// the specified timeout elapses.
+    ///
+    /// Spurious wakeups are possible, so the caller must check the state of the ring buffer again afterward.
+    #[cfg(not(all(feature = "futex", target_os = "linux")))]
+    fn wait_0(&self, read: u32, timeout: Option<Duration>) -> Result<()> {
+        let buffer = self.0.buffer();
 
-        self.seek(position)?;
+        let _waiting = WaitingReceiver::new(buffer.header());
+        let mut lock = buffer.lock()?;
+        if read == buffer.header().write.load(SeqCst)
+            && !self.senders_disconnected()
+            && !self.shut_down()
+        {
+            lock.timed_wait(&self.0, timeout)?;
+        }
 
-        Ok(value)
+        Ok(())
     }
 
-    /// Attempt to read a message, blocking for up to the specified duration if necessary until one becomes
-    /// available.
-    pub fn recv_timeout<T>(&self, timeout: Duration) -> Result<Option<T>>
-    where
-        T: for<'de> Deserialize<'de>,
-    {
-        Ok(
-            if let Some((value, position)) = self.recv_timeout_0(Some(timeout))? {
-                self.seek(position)?;
+    /// Wait until a message is written at `read`, all senders have disconnected, the ring buffer is shut down, or
+    /// the specified timeout elapses.
+    ///
+    /// Spurious wakeups are possible, so the caller must check the state of the ring buffer again afterward.
+    #[cfg(all(feature = "futex", target_os = "linux"))]
+    fn wait_0(&self, read: u32, timeout: Option<Duration>) -> Result<()> {
+        let header = self.0.buffer().header();
 
-                Some(value)
-            } else {
-                None
-            },
-        )
-    }
+        // Load the sequence number before checking the state of the ring buffer so that any notification sent
+        // after the check causes the wait to return immediately.
+        let seq = header.notify_seq.load(Acquire);
 
-    /// Borrows this receiver for deserializing a message with references that refer directly to this
-    /// [`Receiver`](struct.Receiver.html)'s ring buffer rather than copying out of it.
-    ///
-    /// Because those references refer directly to the ring buffer, the read pointer cannot be advanced until the
-    /// lifetime of those references ends.
-    ///
-    /// To ensure the above, the following rules apply:
-    ///
-    /// 1. The underlying [`Receiver`](struct.Receiver.html) cannot be used while a
-    /// [`ZeroCopyContext`](struct.ZeroCopyContext.html) borrows it (enforced at compile time).
-    ///
-    /// 2. References in a message deserialized using a given [`ZeroCopyContext`](struct.ZeroCopyContext.html)
-    /// cannot outlive that instance (enforced at compile time).
-    ///
-    /// 3. A given [`ZeroCopyContext`](struct.ZeroCopyContext.html) can only be used to deserialize a single
-    /// message before it must be discarded since the read pointer is advanced only when the instance is dropped
-    /// (enforced at run time).
-    pub fn zero_copy_context(&mut self) -> ZeroCopyContext {
-        ZeroCopyContext {
-            receiver: self,
-            position: None,
+        if read == header.write.load(Acquire) && !self.senders_disconnected() && !self.shut_down() {
+            futex::wait(&header.notify_seq, seq, timeout)?;
         }
+
+        Ok(())
     }
 
-    fn recv_timeout_0<'a, T: Deserialize<'a>>(
-        &'a self,
+    /// Wait for `frames_size` bytes of contiguous space to become available, fill it with `frame_count` complete
+    /// frames using `write_frames`, and then publish them all at once.
+    ///
+    /// `write_frames` returns the number of bytes it actually used, which may be less than `frames_size`; only
+    /// those bytes are published.
+    fn write_frames_timeout_0(
+        &self,
+        frames_size: u32,
+        frame_count: u32,
+        wait_until_empty: bool,
         timeout: Option<Duration>,
-    ) -> Result<Option<(T, u32)>> {
-        let mut deadline = None;
+        write_frames: impl FnOnce(&mut [u8]) -> Result<u32>,
+    ) -> Result<Option<SendOutcome>> {
+        let buffer = self.0.buffer();
+        let map = self.0.map_mut();
+
+        check_header(buffer.header())?;
+
+        let map_len = map.len();
+
+        // In addition to the frames themselves, we always leave room for a wrap marker (or, equivalently, a gap
+        // between the write and read pointers so they never coincide unless the ring buffer is empty).
+        if BEGINNING as u64 + frames_size as u64 + 4 > map_len as u64 {
+            return Err(Error::MessageTooLarge);
+        }
+
+        let max_messages = buffer.header().max_messages.load(Relaxed);
+
+        if max_messages != 0 && frame_count > max_messages {
+            return Err(Error::MessageTooLarge);
+        }
+
+        if buffer.header().options.load(Relaxed) & OPTION_SINGLE_PRODUCER != 0 {
+            return Err(Error::WrongSenderKind);
+        }
+
+        // The timeout covers acquiring the lock as well as waiting for space, so a slow or stuck sender holding the
+        // lock can't make us block indefinitely.
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut lock = buffer.lock_timeout(timeout)?;
+        let mut waiting = None;
+        let mut blocked_since = None;
+        let mut write;
         loop {
-            if let Some(value_and_position) = self.try_recv_0()? {
-                return Ok(Some(value_and_position));
+            // This is checked while holding the lock, so nothing can be written once `Receiver::resize` has moved
+            // the messages, and senders which were waiting for space find out when it wakes them.
+            if buffer.header().generation.load(Relaxed) != buffer.generation() {
+                return Err(Error::Resized);
             }
 
-            let buffer = self.0 .0.buffer();
+            write = buffer.header().write.load(Relaxed);
+            // The receiver may advance this without holding the lock; see `Receiver::seek`.
+            let read = buffer.header().read.load(SeqCst);
 
-            let mut now = Instant::now();
-            deadline = deadline.or_else(|| timeout.map(|timeout| now + timeout));
+            debug_check_pointers(read, write, map_len);
 
-            let read = buffer.header().read.load(Relaxed);
+            let full =
+                max_messages != 0 && count_frames(map, read, write)? + frame_count > max_messages;
 
-            let mut lock = buffer.lock()?;
-            while read == buffer.header().write.load(Acquire) {
-                if deadline.map(|deadline| deadline > now).unwrap_or(true) {
-                    lock.timed_wait(&self.0 .0, deadline.map(|deadline| deadline - now))?;
+            if full {
+                // Wait for the receiver to consume enough messages (see below).
+            } else if write == read || (write > read && !wait_until_empty) {
+                if (write + frames_size + 4) as usize <= map_len {
+                    break;
+                } else if read != BEGINNING {
+                    assert!(write > BEGINNING);
 
-                    now = Instant::now();
-                } else {
-                    return Ok(None);
+                    bincode::serialize_into(region(map, write, write + 4)?, &0_u32)?;
+                    buffer.header().wrap_count.fetch_add(1, Relaxed);
+                    write = BEGINNING;
+                    buffer.header().write.store(write, SeqCst);
+                    if !receiver_spinning(buffer.header()) {
+                        lock.notify_all()?;
+                        notify_data_ready(buffer);
+                    }
+                    continue;
                 }
+            } else if write + frames_size + 4 <= read && !wait_until_empty {
+                break;
+            }
+
+            // This is checked while holding the lock, so `ShutdownToken::shutdown` can't slip in between the check
+            // and the wait below.
+            if buffer.header().shutdown.load(Relaxed) != 0 {
+                return Err(Error::Interrupted);
+            }
+
+            if waiting.is_none() {
+                // Register as a waiter and then check again before blocking (see above).
+                waiting = Some(WaitingSender::new(buffer.header()));
+                continue;
+            }
+
+            let now = Instant::now();
+            blocked_since = blocked_since.or(Some(now));
+
+            if deadline.map(|deadline| deadline > now).unwrap_or(true) {
+                lock.timed_wait(&self.0, deadline.map(|deadline| deadline - now))?;
+            } else {
+                return Ok(None);
             }
         }
-    }
-}
 
-/// Borrows a [`Receiver`](struct.Receiver.html) for the purpose of doing zero-copy deserialization of messages
-/// containing references.
-///
-/// An instance of this type may only be used to deserialize a single message before it is dropped because the
-/// [`Drop`](https://doc.rust-lang.org/std/ops/trait.Drop.html) implementation is what advances the ring buffer
-/// pointer.  Also, the borrowed [`Receiver`](struct.Receiver.html) may not be used directly while it is borrowed
-/// by a [`ZeroCopyContext`](struct.ZeroCopyContext.html).
-///
-/// Use [`Receiver::zero_copy_context`](struct.Receiver.html#method.zero_copy_context) to create an instance.
-pub struct ZeroCopyContext<'a> {
-    receiver: &'a Receiver,
-    position: Option<u32>,
-}
+        drop(waiting);
 
-impl<'a> ZeroCopyContext<'a> {
-    /// Attempt to read a message without blocking.
-    ///
-    /// This will return `Ok(None)` if there are no messages immediately available.  It will return
-    /// `Err(`[`Error::AlreadyReceived`](enum.Error.html#variant.AlreadyReceived)`))` if this instance has already
-    /// been used to read a message.
-    pub fn try_recv<'b, T: Deserialize<'b>>(&'b mut self) -> Result<Option<T>> {
-        if self.position.is_some() {
-            Err(Error::AlreadyReceived)
-        } else {
-            Ok(
-                if let Some((value, position)) = self.receiver.try_recv_0()? {
-                    self.position = Some(position);
-                    Some(value)
-                } else {
-                    None
-                },
-            )
+        let used = write_frames(region(map, write, write + frames_size)?)?;
+
+        debug_assert!(used <= frames_size);
+
+        let end = write + used;
+
+        // These are only statistics, so there's no need to synchronize them with anything else.
+        buffer
+            .header()
+            .messages_sent
+            .fetch_add(frame_count.into(), Relaxed);
+        buffer.header().bytes_sent.fetch_add(used.into(), Relaxed);
+
+        buffer.header().write.store(end, SeqCst);
+
+        // A spinning receiver will notice the new write pointer by itself, so there's no need for a broadcast.
+        // Senders waiting for space never need to be woken here, since we haven't freed any.
+        if !receiver_spinning(buffer.header()) {
+            lock.notify_all()?;
+            notify_data_ready(buffer);
         }
+
+        Ok(Some(if let Some(blocked_since) = blocked_since {
+            SendOutcome::Blocked {
+                waited: blocked_since.elapsed(),
+            }
+        } else {
+            SendOutcome::Immediate
+        }))
     }
 
-    /// Attempt to read a message, blocking if necessary until one becomes available.
+    /// Sets the permissions of the file backing this ring buffer to `mode`, e.g. `0o660` to allow processes
+    /// running as other users in the file's group to open it.
     ///
-    /// This will return `Err(`[`Error::AlreadyReceived`](enum.Error.html#variant.AlreadyReceived)`))` if this
-    /// instance has already been used to read a message.
-    pub fn recv<'b, T: Deserialize<'b>>(&'b mut self) -> Result<T> {
-        let (value, position) = self.receiver.recv_timeout_0(None)?.unwrap();
+    /// By default, files are created with permissions determined by the process umask (or `0o600` in the case of
+    /// temporary files), which may prevent processes running as other users from opening them.  Note that the
+    /// directory containing the file must also be accessible to those users.
+    ///
+    /// Widening the permissions exposes the ring buffer to every process able to open the file: such a process can
+    /// read all messages sent, send its own, or corrupt the ring buffer, so prefer a dedicated shared group to
+    /// granting access to everyone.  Since this applies after the file has been created, a process which opened it
+    /// in the meantime keeps its access; use
+    /// [`SharedRingBufferBuilder::mode`](struct.SharedRingBufferBuilder.html#method.mode) to set the permissions
+    /// as the file is created instead.
+    #[cfg(unix)]
+    pub fn set_mode(&self, mode: u32) -> Result<()> {
+        Ok(fs::set_permissions(
+            self.name(),
+            Permissions::from_mode(mode),
+        )?)
+    }
 
-        self.position = Some(position);
+    /// Returns the name of the file backing this ring buffer, which may be passed to
+    /// [`SharedRingBuffer::open`](struct.SharedRingBuffer.html#method.open) to create corresponding instances in
+    /// other processes.
+    pub fn name(&self) -> &str {
+        self.0.buffer().path()
+    }
 
-        Ok(value)
+    /// Returns the identifier randomly assigned to this ring buffer when it was created.
+    ///
+    /// All [`SharedRingBuffer`](struct.SharedRingBuffer.html)s (and their [`Sender`](struct.Sender.html)s and
+    /// [`Receiver`](struct.Receiver.html)s) referring to the same ring buffer, in any process, return the same
+    /// identifier, so it may be used to tell handles to different ring buffers apart.
+    pub fn buffer_id(&self) -> u64 {
+        self.0.buffer().header().buffer_id.load(Relaxed)
     }
 
-    /// Attempt to read a message, blocking for up to the specified duration if necessary until one becomes
-    /// available.
+    /// Returns a snapshot of the cumulative statistics kept for this ring buffer since it was created.
     ///
-    /// This will return `Err(`[`Error::AlreadyReceived`](enum.Error.html#variant.AlreadyReceived)`))` if this
-    /// instance has already been used to read a message.
-    pub fn recv_timeout<'b, T: Deserialize<'b>>(
-        &'b mut self,
-        timeout: Duration,
-    ) -> Result<Option<T>> {
-        if self.position.is_some() {
-            Err(Error::AlreadyReceived)
-        } else {
-            Ok(
-                if let Some((value, position)) = self.receiver.recv_timeout_0(Some(timeout))? {
-                    self.position = Some(position);
-                    Some(value)
-                } else {
-                    None
-                },
-            )
+    /// The counters are updated without synchronizing with each other, so a snapshot taken while senders are
+    /// active may, for example, include a message in `messages_sent` but not yet in `bytes_sent`.
+    pub fn stats(&self) -> Stats {
+        let header = self.0.buffer().header();
+
+        Stats {
+            messages_sent: header.messages_sent.load(Relaxed),
+            bytes_sent: header.bytes_sent.load(Relaxed),
+            wrap_count: header.wrap_count.load(Relaxed),
         }
     }
+
+    /// Synchronously write any modified pages of the ring buffer to the file backing it (i.e. `msync` with
+    /// `MS_SYNC`, or `FlushViewOfFile` on Windows).
+    ///
+    /// This is only useful when the file lives on a persistent filesystem (rather than e.g. `/dev/shm` or another
+    /// `tmpfs`) and the ring buffer is being used as a durable queue: after flushing at a checkpoint, messages sent
+    /// but not yet received will still be in the file if the system subsequently crashes, and may be received after
+    /// reopening it using [`SharedRingBuffer::open`](struct.SharedRingBuffer.html#method.open).  Note that, unless
+    /// the ring buffer was created with [`MutexKind::Robust`](enum.MutexKind.html#variant.Robust), a lock held at
+    /// the time of a crash may remain held in the file.
+    pub fn flush_to_disk(&self) -> Result<()> {
+        Ok(self.0.buffer().map().flush()?)
+    }
 }
 
-impl<'a> Drop for ZeroCopyContext<'a> {
-    fn drop(&mut self) {
-        if let Some(position) = self.position.take() {
-            let _ = self.receiver.seek(position);
-        }
+impl fmt::Debug for SharedRingBuffer {
+    /// Formats the name and capacity of the ring buffer along with a snapshot of its read and write pointers, but
+    /// not the messages themselves.
+    ///
+    /// This doesn't take the ring buffer lock, since the receiver moves the read pointer without it anyway (see
+    /// `Receiver::seek`), so the pointers may have moved by the time they are printed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let buffer = self.0.buffer();
+        let read = buffer.header().read.load(Relaxed);
+        let write = buffer.header().write.load(Acquire);
+
+        f.debug_struct("SharedRingBuffer")
+            .field("name", &self.name())
+            .field("size_in_bytes", &self.size_in_bytes())
+            .field("read", &read)
+            .field("write", &write)
+            .field("occupied", &occupied(read, write, buffer.map().len()))
+            .finish()
     }
 }
 
-/// Represents the sending end of an inter-process channel.
-#[derive(Clone)]
-pub struct Sender(SharedRingBuffer);
+/// Represents the receiving end of an inter-process channel, capable of receiving any message type implementing
+/// [`serde::Deserialize`](https://docs.serde.rs/serde/trait.Deserialize.html).
+///
+/// Once every [`Sender`](struct.Sender.html) connected to the ring buffer has been dropped, the receiving methods
+/// continue to return any messages remaining in the ring buffer and then return
+/// `Err(`[`Error::NoSenders`](enum.Error.html#variant.NoSenders)`)` rather than waiting for more.
+pub struct Receiver {
+    buffer: SharedRingBuffer,
+    buffer_id: u64,
+    spin_count: u32,
+    config: Config,
+}
 
-impl Sender {
-    /// Constructs a [`Sender`](struct.Sender.html) from the specified
+impl Receiver {
+    /// Constructs a [`Receiver`](struct.Receiver.html) from the specified
     /// [`SharedRingBuffer`](struct.SharedRingBuffer.html)
+    ///
+    /// See [`ReceiverBuilder`](struct.ReceiverBuilder.html) for additional options.
     pub fn new(buffer: SharedRingBuffer) -> Self {
-        Self(buffer)
+        ReceiverBuilder::new().build(buffer)
     }
 
-    /// Send the specified message, waiting for sufficient contiguous space to become available in the ring buffer
-    /// if necessary.
+    /// Constructs a [`Receiver`](struct.Receiver.html) for an existing ring buffer backed by a file with the
+    /// specified name, without reinitializing it.
     ///
-    /// The serialized size of the message must be greater than zero or else this method will return
-    /// `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))`.  If the serialized size is
-    /// greater than the ring buffer capacity, this method will return
-    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
-    pub fn send(&self, value: &impl Serialize) -> Result<()> {
-        self.send_timeout_0(value, false, None).map(drop)
+    /// This is a shorthand for `Receiver::new(SharedRingBuffer::open(path)?)`, and allows e.g. a supervisor to
+    /// restart a consumer process which then resumes receiving where its predecessor left off, including any
+    /// messages queued by senders in the meantime.  See
+    /// [`SharedRingBuffer::open`](struct.SharedRingBuffer.html#method.open) for how the file is validated.
+    pub fn attach(path: &str) -> Result<Self> {
+        ReceiverBuilder::new().attach(path)
     }
 
-    /// Send the specified message, waiting for sufficient contiguous space to become available in the ring buffer
-    /// if necessary, but only up to the specified timeout.
+    /// Deserialize messages received by this receiver using the specified bincode configuration, which must match
+    /// that of every [`Sender`](struct.Sender.html) for the ring buffer (see
+    /// [`Sender::with_config`](struct.Sender.html#method.with_config)).
     ///
-    /// This will return `Ok(true)` if the message was sent, or `Ok(false)` if it timed out while waiting.
+    /// This is equivalent to [`ReceiverBuilder::config`](struct.ReceiverBuilder.html#method.config).
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Record that this receiver is still alive, for the benefit of senders calling
+    /// [`Sender::peer_stale`](struct.Sender.html#method.peer_stale).
     ///
-    /// The serialized size of the message must be greater than zero or else this method will return
-    /// `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))`.  If the serialized size is
-    /// greater than the ring buffer capacity, this method will return
-    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
-    pub fn send_timeout(&self, value: &impl Serialize, timeout: Duration) -> Result<bool> {
-        self.send_timeout_0(value, false, Some(timeout))
+    /// This is done automatically when the receiver is constructed.  A receiver in a process which may be killed
+    /// without running destructors should call this periodically (e.g. from a timer thread) at an interval
+    /// comfortably shorter than the `max_age` senders use.
+    pub fn heartbeat(&self) {
+        stamp(&self.buffer.0.buffer().header().receiver_heartbeat);
     }
 
-    /// Send the specified message, waiting for the ring buffer to become completely empty first.
+    /// Returns `true` if no [`Sender`](struct.Sender.html) for this ring buffer has recorded a heartbeat (see
+    /// [`Sender::heartbeat`](struct.Sender.html#method.heartbeat)) within `max_age`, e.g. because every sender has
+    /// crashed.
     ///
-    /// This method is appropriate for sending time-sensitive messages where buffering would introduce undesirable
-    /// latency.
+    /// Unlike [`Error::NoSenders`](enum.Error.html#variant.NoSenders), which relies on senders being dropped, this
+    /// can detect senders in processes which were killed without running destructors, provided they heartbeat
+    /// regularly while alive.
+    pub fn peer_stale(&self, max_age: Duration) -> bool {
+        stale(&self.buffer.0.buffer().header().sender_heartbeat, max_age)
+    }
+
+    /// Advance the read pointer to `position`, waking any senders waiting for space.
     ///
-    /// The serialized size of the message must be greater than zero or else this method will return
-    /// `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))`.  If the serialized size
-    /// is greater than the ring buffer capacity, this method will return
-    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
-    pub fn send_when_empty(&self, value: &impl Serialize) -> Result<()> {
-        self.send_timeout_0(value, true, None).map(drop)
+    /// Senders increment `waiting_senders` and then re-check `read` before blocking, so either they will see this
+    /// store or we will see their increment, in which case we must wake them.  This means we only need to take the
+    /// lock when a sender is actually waiting, so a receive usually involves no locking at all.
+    fn seek(&self, position: u32) -> Result<()> {
+        let buffer = self.buffer.0.buffer();
+        let header = buffer.header();
+        header.read.store(position, SeqCst);
+        if header.waiting_senders.load(SeqCst) != 0 {
+            let mut lock = buffer.lock()?;
+            // See `SharedRingBufferBuilder::wake_one`.  Senders waiting for the ring buffer to become empty (e.g.
+            // using `Sender::send_when_empty`) are always woken once it does.
+            if header.options.load(Relaxed) & OPTION_WAKE_ONE != 0
+                && header.write.load(Relaxed) != position
+            {
+                lock.notify_one()?;
+            } else {
+                lock.notify_all()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of messages currently queued in the ring buffer, without deserializing any of them.
+    ///
+    /// This walks the size words of the queued frames, so it takes time proportional to the number of messages.
+    /// The result is a snapshot: senders may have added more messages by the time the caller acts on it.  No lock
+    /// is needed for a consistent snapshot since only this receiver moves the read pointer and senders only
+    /// publish a new write pointer once the frames preceding it are complete.
+    pub fn available_messages(&self) -> Result<usize> {
+        let buffer = self.buffer.0.buffer();
+        let slice = buffer.map().as_ref();
+
+        let read = buffer.header().read.load(Relaxed);
+        let write = buffer.header().write.load(Acquire);
+
+        debug_check_pointers(read, write, slice.len());
+
+        Ok(count_frames(slice, read, write)? as usize)
+    }
+
+    /// Returns the number of bytes currently occupied in the ring buffer, including per-message framing and any
+    /// space left unused at the end by a sender which wrapped around to the beginning.
+    ///
+    /// Together with [`Receiver::capacity`](#method.capacity), this gives the utilization of the ring buffer, e.g.
+    /// to detect when senders are outpacing the receiver.  As with
+    /// [`Receiver::available_messages`](#method.available_messages), the result is a snapshot taken without locking.
+    pub fn len(&self) -> usize {
+        let buffer = self.buffer.0.buffer();
+        let map_len = buffer.map().len();
+
+        let read = buffer.header().read.load(Relaxed);
+        let write = buffer.header().write.load(Acquire);
+
+        debug_check_pointers(read, write, map_len);
+
+        occupied(read, write, map_len) as usize
+    }
+
+    /// Returns `true` if the ring buffer is empty, i.e. [`Receiver::len`](#method.len) would return zero.
+    ///
+    /// Note that a ring buffer which is not empty may still hold no messages if all it holds is a wrap marker, in
+    /// which case receiving will skip it and find nothing.
+    pub fn is_empty(&self) -> bool {
+        let header = self.buffer.0.buffer().header();
+        header.read.load(Relaxed) == header.write.load(Acquire)
+    }
+
+    /// Returns the maximum value [`Receiver::len`](#method.len) can approach, i.e. the size of the data region of the
+    /// ring buffer.
+    ///
+    /// This is a few bytes larger than
+    /// [`SharedRingBuffer::size_in_bytes`](struct.SharedRingBuffer.html#method.size_in_bytes), which excludes the
+    /// framing of the largest message and the space reserved to tell a full ring buffer from an empty one.
+    pub fn capacity(&self) -> usize {
+        self.buffer.0.buffer().map().len() - BEGINNING as usize
+    }
+
+    /// Returns a snapshot of the cumulative statistics for this receiver's ring buffer; see
+    /// [`SharedRingBuffer::stats`](struct.SharedRingBuffer.html#method.stats).
+    pub fn stats(&self) -> Stats {
+        self.buffer.stats()
+    }
+
+    /// Discard every message currently queued in the ring buffer, e.g. to start afresh after an error rather than
+    /// work through the backlog, waking any senders waiting for space.
+    ///
+    /// This holds the ring buffer lock, which senders hold while writing, so a message being sent concurrently is
+    /// either discarded in full or left for the next receive, never torn.  The read and write pointers are both
+    /// moved back to the beginning of the data region, so even the largest message fits afterward without waiting
+    /// for a wrap marker to be received.  Single-producer ring buffers are the exception, since an
+    /// [`SpscSender`](struct.SpscSender.html) moves the write pointer without the lock: for those, only the read
+    /// pointer moves, skipping to the last message published.  Receives then wait for messages sent afterward as
+    /// usual.
+    pub fn clear(&self) -> Result<()> {
+        let buffer = self.buffer.0.buffer();
+        let header = buffer.header();
+
+        let mut lock = buffer.lock()?;
+
+        let write = header.write.load(Acquire);
+
+        debug_check_pointers(header.read.load(Relaxed), write, buffer.map().len());
+
+        if header.options.load(Relaxed) & OPTION_SINGLE_PRODUCER != 0 {
+            header.read.store(write, SeqCst);
+        } else {
+            header.write.store(BEGINNING, SeqCst);
+            header.read.store(BEGINNING, SeqCst);
+        }
+
+        lock.notify_all()
+    }
+
+    /// Move every message currently queued in this receiver's ring buffer to the end of `new`, and then switch
+    /// this receiver over to `new`, e.g. to migrate to a larger ring buffer without losing messages.
+    ///
+    /// The messages are copied as is (without deserializing them) and published in `new` all at once.  If `new`
+    /// does not currently have enough contiguous space for all of them, this returns `Ok(false)` and changes
+    /// nothing, since waiting would never end with nobody receiving from `new`.  If they would not fit even in an
+    /// empty `new`, this returns `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`)`.
+    ///
+    /// Senders must be redirected to `new` separately.  Any messages they send to the old ring buffer after this
+    /// returns will not be received, while any they sent to `new` before this was called will be received ahead of
+    /// the migrated messages.
+    pub fn migrate_to(&mut self, new: SharedRingBuffer) -> Result<bool> {
+        let buffer = self.buffer.0.buffer();
+
+        check_header(buffer.header())?;
+
+        let slice = buffer.map().as_ref();
+        let mut read = buffer.header().read.load(Relaxed);
+        let write = buffer.header().write.load(Acquire);
+
+        debug_check_pointers(read, write, slice.len());
+
+        let mut messages = Vec::new();
+        while read != write {
+            let start = read + 4;
+            let size = bincode::deserialize::<u32>(
+                slice
+                    .get(read as usize..start as usize)
+                    .ok_or(Error::CorruptBuffer)?,
+            )?;
+            if size == EMPTY_MESSAGE {
+                messages.push((UNCOMPRESSED, &[][..]));
+                read = start;
+            } else if size > 0 {
+                let end = start + size;
+                messages.push(check_frame_stored(
+                    slice
+                        .get(start as usize..end as usize)
+                        .ok_or(Error::CorruptBuffer)?,
+                    self.config.fingerprint(),
+                )?);
+                read = end;
+            } else if write < read {
+                read = BEGINNING;
+            } else {
+                return Err(Error::CorruptBuffer);
+            }
+        }
+
+        if !messages.is_empty() {
+            let frames_size = messages
+                .iter()
+                .try_fold(0_u32, |total, (_, message)| {
+                    total.checked_add(frame_size(u32::try_from(message.len()).ok()?)?)
+                })
+                .ok_or(Error::MessageTooLarge)?;
+
+            let frame_count = u32::try_from(messages.len()).map_err(|_| Error::MessageTooLarge)?;
+
+            let write_messages = |mut frames: &mut [u8]| {
+                for &(marker, message) in &messages {
+                    let size = message.len() as u32;
+                    let (frame, rest) = frames
+                        .split_at_mut(frame_size(size).ok_or(Error::MessageTooLarge)? as usize);
+                    write_frame(frame, size, self.config.fingerprint(), |slice| {
+                        slice.copy_from_slice(message);
+                        Ok(())
+                    })?;
+                    // Compressed messages stay compressed:
+                    mark_compression(frame, marker);
+                    frames = rest;
+                }
+                Ok(frames_size)
+            };
+
+            let written = loop {
+                // Don't wait for space (see above), but do wait for any sender briefly holding the lock.
+                match new.write_frames_timeout_0(
+                    frames_size,
+                    frame_count,
+                    false,
+                    Some(Duration::from_secs(0)),
+                    write_messages,
+                ) {
+                    Err(Error::LockTimeout) => thread::yield_now(),
+                    result => break result?,
+                }
+            };
+
+            if written.is_none() {
+                return Ok(false);
+            }
+
+            self.seek(read)?;
+        }
+
+        buffer.header().receiver_alive.store(0, Release);
+
+        new.0.buffer().header().receiver_alive.store(1, Release);
+        self.buffer_id = new.buffer_id();
+        self.buffer = new;
+        self.heartbeat();
+
+        Ok(true)
+    }
+
+    /// Grow this receiver's ring buffer in place so that it can hold messages of up to `size_in_bytes`, keeping
+    /// any queued messages.
+    ///
+    /// The file backing the ring buffer is extended and remapped, and the queued messages are moved to the
+    /// beginning of the data region, all while holding the ring buffer lock.  Other handles to the ring buffer
+    /// (including clones of this receiver's [`SharedRingBuffer`](struct.SharedRingBuffer.html) in this process)
+    /// still map only the original part of the file, so every [`Sender`](struct.Sender.html) must reconnect: from
+    /// now on, sends using existing senders (including any waiting for space) return
+    /// `Err(`[`Error::Resized`](enum.Error.html#variant.Resized)`)`, after which the caller should open the file
+    /// again by name and construct a new sender.  Messages sent before the resize are received as usual.
+    ///
+    /// Ring buffers can't be shrunk, since other processes may still be using the original mapping, so this
+    /// returns `Err(`[`Error::BufferTooSmall`](enum.Error.html#variant.BufferTooSmall)`)` if `size_in_bytes` is
+    /// smaller than [`SharedRingBuffer::size_in_bytes`](struct.SharedRingBuffer.html#method.size_in_bytes).
+    /// Single-producer ring buffers can't be resized either, since an [`SpscSender`](struct.SpscSender.html)
+    /// writes without taking the lock, so this returns
+    /// `Err(`[`Error::WrongSenderKind`](enum.Error.html#variant.WrongSenderKind)`)` for those.
+    pub fn resize(&mut self, size_in_bytes: u32) -> Result<()> {
+        let len = file_len(size_in_bytes)?;
+        let buffer = self.buffer.0.buffer();
+        let header = buffer.header();
+
+        check_header(header)?;
+
+        if header.options.load(Relaxed) & OPTION_SINGLE_PRODUCER != 0 {
+            return Err(Error::WrongSenderKind);
+        }
+
+        if len < buffer.map().len() as u64 {
+            return Err(Error::BufferTooSmall);
+        }
+
+        let mut lock = buffer.lock()?;
+
+        // Copy the queued frames out, leaving behind any wrap marker, since the new mapping overlaps the old one.
+        let slice = buffer.map().as_ref();
+        let mut read = header.read.load(Relaxed);
+        let write = header.write.load(Relaxed);
+
+        debug_check_pointers(read, write, slice.len());
+
+        let mut frames = Vec::with_capacity(occupied(read, write, slice.len()) as usize);
+        while read != write {
+            let start = read + 4;
+            let size = bincode::deserialize::<u32>(
+                slice
+                    .get(read as usize..start as usize)
+                    .ok_or(Error::CorruptBuffer)?,
+            )?;
+            let end = if size == EMPTY_MESSAGE {
+                start
+            } else if size > 0 {
+                start + size
+            } else if write < read {
+                read = BEGINNING;
+                continue;
+            } else {
+                return Err(Error::CorruptBuffer);
+            };
+            frames.extend_from_slice(
+                slice
+                    .get(read as usize..end as usize)
+                    .ok_or(Error::CorruptBuffer)?,
+            );
+            read = end;
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(buffer.path())?;
+        file.set_len(len)?;
+
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        let end = BEGINNING + frames.len() as u32;
+        map[BEGINNING as usize..end as usize].copy_from_slice(&frames);
+
+        // The header is shared by both mappings, so these updates are visible to everyone.
+        header.read.store(BEGINNING, SeqCst);
+        header.write.store(end, SeqCst);
+        header.generation.fetch_add(1, SeqCst);
+
+        // Wake any senders waiting for space so they notice the resize.
+        lock.notify_all()?;
+        drop(lock);
+
+        let mut resized = Buffer::try_new(buffer.path(), map, None)?;
+        resized.keep_alive(self.buffer.0.clone());
+        self.buffer = SharedRingBuffer(View::try_new(Arc::new(UnsafeCell::new(resized)))?);
+
+        Ok(())
+    }
+
+    /// Attempt to read a message without blocking.
+    ///
+    /// This will return `Ok(None)` if there are no messages immediately available.
+    pub fn try_recv<T>(&self) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        Ok(if let Some((value, position)) = self.try_recv_0()? {
+            self.seek(position)?;
+
+            Some(value)
+        } else {
+            None
+        })
+    }
+
+    /// Attempt to read a message without blocking, leaving it in the ring buffer.
+    ///
+    /// This will return `Ok(None)` if there are no messages immediately available.  Otherwise, the next call to
+    /// [`Receiver::try_recv`](#method.try_recv), [`Receiver::recv`](#method.recv), etc. will receive the same
+    /// message, e.g. so that a dispatcher can inspect it before deciding how to handle it.  See
+    /// [`Receiver::try_recv_raw`](#method.try_recv_raw) to inspect the serialized bytes without copying them.
+    pub fn peek<T>(&self) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        Ok(self.try_recv_0()?.map(|(value, _)| value))
+    }
+
+    /// Attempt to read a message without blocking or deserializing it.
+    ///
+    /// This will return `Ok(None)` if there are no messages immediately available.  Otherwise, it returns a
+    /// [`RawMessage`](struct.RawMessage.html) which borrows the serialized bytes of the message directly from the
+    /// ring buffer, e.g. so that the caller may inspect a type tag before deciding how to deserialize it.  The read
+    /// pointer is advanced past the message when the [`RawMessage`](struct.RawMessage.html) is dropped or
+    /// committed, or left in place if it is aborted.
+    ///
+    /// This neither allocates nor deserializes, making it the lowest-overhead way to poll for messages.
+    pub fn try_recv_raw(&mut self) -> Result<Option<RawMessage<'_>>> {
+        Ok(if let Some((bytes, position)) = self.try_recv_raw_0()? {
+            Some(RawMessage {
+                receiver: self,
+                bytes,
+                position: Some(position),
+            })
+        } else {
+            None
+        })
+    }
+
+    fn try_recv_0<T>(&self) -> Result<Option<(T, u32)>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        Ok(
+            if let Some((message, position)) = self.try_recv_frame_0()? {
+                Some((self.config.deserialize(&message)?, position))
+            } else {
+                None
+            },
+        )
+    }
+
+    fn try_recv_ref_0<'a, T: Deserialize<'a>>(&'a self) -> Result<Option<(T, u32)>> {
+        Ok(if let Some((bytes, position)) = self.try_recv_raw_0()? {
+            Some((self.config.deserialize(bytes)?, position))
+        } else {
+            None
+        })
+    }
+
+    /// Like `try_recv_frame_0`, but borrowing the message directly from the ring buffer, which isn't possible if
+    /// it was compressed.
+    fn try_recv_raw_0(&self) -> Result<Option<(&[u8], u32)>> {
+        self.try_recv_raw_from_0(
+            self.buffer.0.buffer().header().read.load(Relaxed),
+            |position| self.seek(position),
+        )
+    }
+
+    fn try_recv_raw_from_0(
+        &self,
+        read: u32,
+        skip: impl Fn(u32) -> Result<()>,
+    ) -> Result<Option<(&[u8], u32)>> {
+        match self.try_recv_frame_from_0(read, skip)? {
+            Some((Cow::Borrowed(bytes), position)) => Ok(Some((bytes, position))),
+            Some((Cow::Owned(_), _)) => Err(Error::Compressed),
+            None => Ok(None),
+        }
+    }
+
+    fn try_recv_frame_0(&self) -> Result<Option<(Cow<'_, [u8]>, u32)>> {
+        self.try_recv_frame_from_0(
+            self.buffer.0.buffer().header().read.load(Relaxed),
+            |position| self.seek(position),
+        )
+    }
+
+    /// Read the frame at `read`; see `SharedRingBuffer::read_frame_0`.
+    ///
+    /// `skip` normally advances the read pointer (i.e. `Receiver::seek`).  A `ZeroCopyBatch` reads ahead of the read
+    /// pointer instead, and so must not advance it, since that would release messages which may still be borrowed.
+    fn try_recv_frame_from_0(
+        &self,
+        read: u32,
+        skip: impl Fn(u32) -> Result<()>,
+    ) -> Result<Option<(Cow<'_, [u8]>, u32)>> {
+        debug_assert_eq!(
+            self.buffer_id,
+            self.buffer.buffer_id(),
+            "receiver used with wrong ring buffer"
+        );
+
+        self.buffer
+            .read_frame_0(read, self.config.fingerprint(), skip)
+    }
+
+    /// Attempt to read a message, blocking if necessary until one becomes available.
+    pub fn recv<T>(&self) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let (value, position) = self.recv_0(Self::try_recv_0)?;
+
+        self.seek(position)?;
+
+        Ok(value)
+    }
+
+    /// Read messages, blocking as necessary, until one satisfies `predicate`, and return it.
+    ///
+    /// Messages which don't satisfy `predicate` are discarded, e.g. to skip ahead to a marker message when
+    /// resynchronizing after an error.  If a message can't be deserialized as a `T`, the error is returned and the
+    /// message is left in the ring buffer.
+    pub fn recv_filtered<T>(&self, mut predicate: impl FnMut(&T) -> bool) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        loop {
+            let (value, position) = self.recv_0(Self::try_recv_0)?;
+
+            self.seek(position)?;
+
+            if predicate(&value) {
+                break Ok(value);
+            }
+        }
+    }
+
+    /// Attempt to read a message, blocking for up to the specified duration if necessary until one becomes
+    /// available.
+    ///
+    /// A zero duration is equivalent to [`Receiver::try_recv`](struct.Receiver.html#method.try_recv), i.e. it
+    /// neither waits nor acquires the ring buffer lock.
+    pub fn recv_timeout<T>(&self, timeout: Duration) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        Ok(
+            if let Some((value, position)) = self.recv_timeout_0(Self::try_recv_0, Some(timeout))? {
+                self.seek(position)?;
+
+                Some(value)
+            } else {
+                None
+            },
+        )
+    }
+
+    /// Attempt to read a message, blocking until the specified deadline if necessary until one becomes available.
+    ///
+    /// This is equivalent to [`Receiver::recv_timeout`](#method.recv_timeout) with the time remaining until
+    /// `deadline`, but avoids the drift which comes from recomputing that duration on each call when receiving in
+    /// a loop with a fixed overall deadline.  A deadline which has already passed is equivalent to
+    /// [`Receiver::try_recv`](#method.try_recv).
+    pub fn recv_deadline<T>(&self, deadline: Instant) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        Ok(
+            if let Some((value, position)) =
+                self.recv_deadline_0(Self::try_recv_0, Some(deadline))?
+            {
+                self.seek(position)?;
+
+                Some(value)
+            } else {
+                None
+            },
+        )
+    }
+
+    /// Read a message as raw bytes, blocking if necessary until one becomes available, without deserializing it.
+    ///
+    /// The bytes are exactly those written by the sender (not including any framing), regardless of the message
+    /// type, so they may be forwarded elsewhere (e.g. using
+    /// [`Sender::send_bytes`](struct.Sender.html#method.send_bytes)) or deserialized later.
+    pub fn recv_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.recv_into(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Read a message as raw bytes into `buffer`, blocking if necessary until one becomes available, without
+    /// deserializing it.
+    ///
+    /// This is like [`Receiver::recv_bytes`](struct.Receiver.html#method.recv_bytes), except that `buffer` is
+    /// cleared and then extended with the message, reusing its allocation, so a caller receiving many messages
+    /// need not allocate for each one.  Note that this only matters for owned data: zero-copy receives (e.g.
+    /// [`Receiver::recv_bytes_ref`](struct.Receiver.html#method.recv_bytes_ref) or
+    /// [`Receiver::recv_ref`](struct.Receiver.html#method.recv_ref) with a `&[u8]` or `&str` field) already avoid
+    /// copying the message at all.
+    pub fn recv_into(&self, buffer: &mut Vec<u8>) -> Result<()> {
+        let (message, position) = self.recv_0(Self::try_recv_frame_0)?;
+        buffer.clear();
+        buffer.extend_from_slice(&message);
+        self.seek(position)
+    }
+
+    /// Read a message as raw bytes, blocking if necessary until one becomes available, without copying or
+    /// deserializing it.
+    ///
+    /// This is the blocking counterpart of [`Receiver::try_recv_raw`](struct.Receiver.html#method.try_recv_raw):
+    /// the returned [`RawMessage`](struct.RawMessage.html) borrows the bytes directly from the ring buffer, and the
+    /// read pointer is advanced past the message when it is dropped or committed.
+    pub fn recv_bytes_ref(&mut self) -> Result<RawMessage<'_>> {
+        let (bytes, position) = self.recv_0(Self::try_recv_raw_0)?;
+
+        Ok(RawMessage {
+            receiver: self,
+            bytes,
+            position: Some(position),
+        })
+    }
+
+    /// Returns an iterator which receives messages using [`Receiver::recv`](struct.Receiver.html#method.recv),
+    /// blocking as necessary.
+    ///
+    /// Errors are yielded as `Some(Err(..))`, after which iteration may continue.  The iterator ends once all
+    /// senders have disconnected (or the ring buffer has been shut down) and every message they sent has been
+    /// received, so `for message in receiver.iter::<T>() { ... }` replaces the usual receive loop and composes with
+    /// iterator adapters such as `take` and `filter`.
+    pub fn iter<T>(&self) -> impl Iterator<Item = Result<T>> + '_
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        iter::from_fn(move || match self.recv() {
+            Err(Error::NoSenders) | Err(Error::Interrupted) => None,
+            result => Some(result),
+        })
+    }
+
+    /// Returns an iterator which receives messages as raw bytes using
+    /// [`Receiver::recv_bytes`](struct.Receiver.html#method.recv_bytes), blocking as necessary.
+    ///
+    /// The iterator ends once all senders have disconnected (or the ring buffer has been shut down) and every
+    /// message they sent has been received.  This is useful for schema-agnostic tools which relay or dump messages
+    /// without knowing their types.
+    pub fn byte_iter(&self) -> impl Iterator<Item = Result<Vec<u8>>> + '_ {
+        iter::from_fn(move || match self.recv_bytes() {
+            Err(Error::NoSenders) | Err(Error::Interrupted) => None,
+            result => Some(result),
+        })
+    }
+
+    /// Returns an iterator which receives every message immediately available using
+    /// [`Receiver::try_recv`](struct.Receiver.html#method.try_recv), without blocking.
+    ///
+    /// The iterator ends as soon as the ring buffer is empty (including when all senders have disconnected or it has
+    /// been shut down), or after yielding the first error.  This is convenient for event loops which process
+    /// everything available and then go back to waiting.
+    pub fn drain<T>(&self) -> impl Iterator<Item = Result<T>> + '_
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut failed = false;
+        iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+
+            match self.try_recv() {
+                Ok(value) => value.map(Ok),
+                Err(Error::NoSenders) | Err(Error::Interrupted) => None,
+                Err(e) => {
+                    failed = true;
+                    Some(Err(e))
+                }
+            }
+        })
+    }
+
+    /// Attempt to read a message sent using [`Sender::send_checked`](struct.Sender.html#method.send_checked)
+    /// without blocking, verifying that its schema fingerprint matches `T`'s.
+    ///
+    /// On mismatch, this returns `Err(`[`Error::SchemaMismatch`](enum.Error.html#variant.SchemaMismatch)`)` and
+    /// leaves the message in the ring buffer.  It may be skipped using
+    /// [`Receiver::try_recv_raw`](struct.Receiver.html#method.try_recv_raw) if desired.
+    pub fn try_recv_checked<T>(&self) -> Result<Option<T>>
+    where
+        T: Schema + for<'de> Deserialize<'de>,
+    {
+        Ok(
+            if let Some((message, position)) = self.try_recv_frame_0()? {
+                let value = schema::deserialize_checked(self.config, &message)?;
+                self.seek(position)?;
+                Some(value)
+            } else {
+                None
+            },
+        )
+    }
+
+    /// Attempt to read a message sent using [`Sender::send_checked`](struct.Sender.html#method.send_checked),
+    /// blocking if necessary until one becomes available, and verifying that its schema fingerprint matches `T`'s.
+    ///
+    /// See [`Receiver::try_recv_checked`](struct.Receiver.html#method.try_recv_checked) for how a mismatch is
+    /// handled.
+    pub fn recv_checked<T>(&self) -> Result<T>
+    where
+        T: Schema + for<'de> Deserialize<'de>,
+    {
+        loop {
+            if let Some(value) = self.try_recv_checked()? {
+                return Ok(value);
+            }
+
+            self.wait_0(None)?;
+        }
+    }
+
+    /// Attempt to read a message sent using [`Sender::send_tagged`](struct.Sender.html#method.send_tagged) without
+    /// blocking.
+    ///
+    /// The returned [`TaggedMessage`](struct.TaggedMessage.html) holds a copy of the message, whose tag may be
+    /// inspected before deserializing it.
+    pub fn try_recv_tagged(&self) -> Result<Option<TaggedMessage>> {
+        Ok(if let Some((bytes, position)) = self.try_recv_frame_0()? {
+            let message = TaggedMessage::new(self.config, &bytes)?;
+            self.seek(position)?;
+            Some(message)
+        } else {
+            None
+        })
+    }
+
+    /// Attempt to read a message sent using [`Sender::send_tagged`](struct.Sender.html#method.send_tagged),
+    /// blocking if necessary until one becomes available.
+    ///
+    /// See [`Tagged`](trait.Tagged.html) for an example.
+    pub fn recv_tagged(&self) -> Result<TaggedMessage> {
+        loop {
+            if let Some(message) = self.try_recv_tagged()? {
+                return Ok(message);
+            }
+
+            self.wait_0(None)?;
+        }
+    }
+
+    /// Attempt to read a message, blocking if necessary until one becomes available, and return it in a guard
+    /// which may borrow directly from the ring buffer (e.g. `recv_ref::<&str>()`).
+    ///
+    /// This is a shorthand for `zero_copy_context().recv()` for the common case of receiving a single message: the
+    /// read pointer is advanced past the message when the returned [`RecvRef`](struct.RecvRef.html) is dropped,
+    /// and this receiver cannot be used until then (enforced at compile time).
+    pub fn recv_ref<'a, T: Deserialize<'a>>(&'a mut self) -> Result<RecvRef<'a, T>> {
+        let (value, position) = self.recv_0(Self::try_recv_ref_0)?;
+
+        Ok(RecvRef {
+            receiver: self,
+            value,
+            position,
+        })
+    }
+
+    /// Borrows this receiver for deserializing a message with references that refer directly to this
+    /// [`Receiver`](struct.Receiver.html)'s ring buffer rather than copying out of it.
+    ///
+    /// Because those references refer directly to the ring buffer, the read pointer cannot be advanced until the
+    /// lifetime of those references ends.
+    ///
+    /// To ensure the above, the following rules apply:
+    ///
+    /// 1. The underlying [`Receiver`](struct.Receiver.html) cannot be used while a
+    /// [`ZeroCopyContext`](struct.ZeroCopyContext.html) borrows it (enforced at compile time).
+    ///
+    /// 2. References in a message deserialized using a given [`ZeroCopyContext`](struct.ZeroCopyContext.html)
+    /// cannot outlive that instance (enforced at compile time).
+    ///
+    /// 3. A given [`ZeroCopyContext`](struct.ZeroCopyContext.html) can only be used to deserialize a single
+    /// message before it must be either discarded or explicitly committed or aborted since the read pointer is
+    /// advanced only when the instance is dropped or committed (enforced at run time).
+    ///
+    /// These rules are what make zero-copy deserialization sound, so the compile-time ones are checked by the
+    /// examples below, each of which must fail to compile.  Using the receiver while a context borrows it:
+    ///
+    /// ```compile_fail,E0502
+    /// # fn main() -> ipmpsc::Result<()> {
+    /// # let (_, buffer) = ipmpsc::SharedRingBuffer::create_temp(32)?;
+    /// let mut rx = ipmpsc::Receiver::new(buffer);
+    /// let mut context = rx.zero_copy_context();
+    /// rx.try_recv::<u32>()?;
+    /// context.try_recv::<&str>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Letting a reference escape the context it was received from:
+    ///
+    /// ```compile_fail,E0597
+    /// # fn main() -> ipmpsc::Result<()> {
+    /// # let (_, buffer) = ipmpsc::SharedRingBuffer::create_temp(32)?;
+    /// let mut rx = ipmpsc::Receiver::new(buffer);
+    /// let message: Option<&str>;
+    /// {
+    ///     let mut context = rx.zero_copy_context();
+    ///     message = context.try_recv()?;
+    /// }
+    /// println!("{:?}", message);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Receiving a second message from a context while a reference into the first is still live:
+    ///
+    /// ```compile_fail,E0499
+    /// # fn main() -> ipmpsc::Result<()> {
+    /// # let (_, buffer) = ipmpsc::SharedRingBuffer::create_temp(32)?;
+    /// let mut rx = ipmpsc::Receiver::new(buffer);
+    /// let mut context = rx.zero_copy_context();
+    /// let first = context.try_recv::<&str>()?;
+    /// let second = context.try_recv::<&str>()?;
+    /// println!("{:?} {:?}", first, second);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn zero_copy_context(&mut self) -> ZeroCopyContext {
+        ZeroCopyContext {
+            receiver: self,
+            position: None,
+        }
+    }
+
+    /// Borrows this receiver for deserializing several messages with references that refer directly to this
+    /// [`Receiver`](struct.Receiver.html)'s ring buffer, all of which remain valid until the
+    /// [`ZeroCopyBatch`](struct.ZeroCopyBatch.html) is dropped.
+    ///
+    /// This is like [`Receiver::zero_copy_context`](#method.zero_copy_context), except that the read pointer is
+    /// advanced past every message received using the batch at once, when it is dropped or committed.
+    ///
+    /// As with a [`ZeroCopyContext`](struct.ZeroCopyContext.html), references into a message may not outlive the
+    /// batch it was received from:
+    ///
+    /// ```compile_fail,E0505
+    /// # fn main() -> ipmpsc::Result<()> {
+    /// # let (_, buffer) = ipmpsc::SharedRingBuffer::create_temp(32)?;
+    /// let mut rx = ipmpsc::Receiver::new(buffer);
+    /// let batch = rx.zero_copy_batch();
+    /// let message = batch.try_recv::<&str>()?;
+    /// batch.commit()?;
+    /// println!("{:?}", message);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn zero_copy_batch(&mut self) -> ZeroCopyBatch<'_> {
+        let position = self.buffer.0.buffer().header().read.load(Relaxed);
+
+        ZeroCopyBatch {
+            receiver: self,
+            start: position,
+            position: Cell::new(position),
+            received: Cell::new(0),
+        }
+    }
+
+    /// Receive messages until `handler` returns `ControlFlow::Break` or every
+    /// [`Sender`](struct.Sender.html) has disconnected, blocking while waiting for each one.
+    ///
+    /// This is a push-style alternative to calling [`Receiver::recv`](struct.Receiver.html#method.recv) in a
+    /// loop.  It returns `Ok(())` once `handler` asks to stop or all senders have disconnected (or the ring buffer
+    /// has been shut down) and every message they sent has been handled, or an error if receiving or deserializing
+    /// a message fails.
+    pub fn run<T>(self, mut handler: impl FnMut(T) -> ControlFlow<()>) -> Result<()>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        loop {
+            match self.recv() {
+                Ok(value) => {
+                    if handler(value).is_break() {
+                        break Ok(());
+                    }
+                }
+                Err(Error::NoSenders) | Err(Error::Interrupted) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        }
+    }
+
+    /// Like [`Receiver::run`](struct.Receiver.html#method.run), but for zero-copy deserialization.
+    ///
+    /// `handler` is called with a fresh [`ZeroCopyContext`](struct.ZeroCopyContext.html) for each message and
+    /// should receive exactly one message using it, e.g. `|context| { let s = context.recv::<&str>()?; ... }`.
+    /// Errors returned by `handler` are propagated, except for
+    /// `Err(`[`Error::NoSenders`](enum.Error.html#variant.NoSenders)`)` and
+    /// `Err(`[`Error::Interrupted`](enum.Error.html#variant.Interrupted)`)`, which end the loop successfully.
+    pub fn run_zero_copy(
+        mut self,
+        mut handler: impl FnMut(&mut ZeroCopyContext<'_>) -> Result<ControlFlow<()>>,
+    ) -> Result<()> {
+        loop {
+            match handler(&mut self.zero_copy_context()) {
+                Ok(ControlFlow::Continue(())) => (),
+                Ok(ControlFlow::Break(())) | Err(Error::NoSenders) | Err(Error::Interrupted) => {
+                    break Ok(())
+                }
+                Err(e) => break Err(e),
+            }
+        }
+    }
+
+    /// Call `try_recv` (e.g. `Receiver::try_recv_0`) until it returns a message, blocking in between.
+    fn recv_0<'a, T>(
+        &'a self,
+        try_recv: impl Fn(&'a Self) -> Result<Option<(T, u32)>>,
+    ) -> Result<(T, u32)> {
+        loop {
+            if let Some(value_and_position) = try_recv(self)? {
+                return Ok(value_and_position);
+            }
+
+            self.wait_0(None)?;
+        }
+    }
+
+    /// Call `try_recv` (e.g. `Receiver::try_recv_0`) until it returns a message or `timeout` elapses, blocking in
+    /// between.
+    fn recv_timeout_0<'a, T>(
+        &'a self,
+        try_recv: impl Fn(&'a Self) -> Result<Option<(T, u32)>>,
+        timeout: Option<Duration>,
+    ) -> Result<Option<(T, u32)>> {
+        if timeout == Some(Duration::from_secs(0)) {
+            return try_recv(self);
+        }
+
+        self.recv_deadline_0(
+            try_recv,
+            timeout.and_then(|timeout| Instant::now().checked_add(timeout)),
+        )
+    }
+
+    /// Call `try_recv` (e.g. `Receiver::try_recv_0`) until it returns a message or `deadline` passes, blocking in
+    /// between.
+    fn recv_deadline_0<'a, T>(
+        &'a self,
+        try_recv: impl Fn(&'a Self) -> Result<Option<(T, u32)>>,
+        deadline: Option<Instant>,
+    ) -> Result<Option<(T, u32)>> {
+        loop {
+            if let Some(value_and_position) = try_recv(self)? {
+                return Ok(Some(value_and_position));
+            }
+
+            let now = Instant::now();
+
+            if deadline.map(|deadline| deadline > now).unwrap_or(true) {
+                self.wait_0(deadline.map(|deadline| deadline - now))?;
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Busy-wait for up to `spin_count` iterations for the write pointer to move away from `read`, returning `true`
+    /// if it did.
+    ///
+    /// `Header::receiver_spinning` is set for the duration so that senders can skip waking us (see
+    /// `receiver_spinning`).  It is cleared before returning, and in particular before the caller re-checks the
+    /// write pointer prior to blocking, so a sender which skipped the wakeup is guaranteed to have published a write
+    /// pointer we will see.
+    fn spin(&self, read: u32) -> bool {
+        if self.spin_count == 0 {
+            return false;
+        }
+
+        let header = self.buffer.0.buffer().header();
+
+        header.receiver_spinning.store(1, SeqCst);
+
+        let ready = (0..self.spin_count).any(|_| {
+            hint::spin_loop();
+            header.write.load(SeqCst) != read
+        });
+
+        header.receiver_spinning.store(0, SeqCst);
+
+        ready
+    }
+
+    /// Wait until the ring buffer is non-empty, all senders have disconnected, or the specified timeout elapses.
+    ///
+    /// Spurious wakeups are possible, so the caller must check the state of the ring buffer again afterward.
+    fn wait_0(&self, timeout: Option<Duration>) -> Result<()> {
+        self.wait_from_0(self.buffer.0.buffer().header().read.load(Relaxed), timeout)
+    }
+
+    /// Like `wait_0`, but waits for a message to be written at `read` rather than at the read pointer, e.g. after
+    /// the messages preceding it have been received by a `ZeroCopyBatch`.
+    fn wait_from_0(&self, read: u32, timeout: Option<Duration>) -> Result<()> {
+        if self.spin(read) {
+            return Ok(());
+        }
+
+        self.buffer.wait_0(read, timeout)
+    }
+}
+
+impl fmt::Debug for Receiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("buffer", &self.buffer)
+            .field("spin_count", &self.spin_count)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        self.buffer
+            .0
+            .buffer()
+            .header()
+            .receiver_alive
+            .store(0, Release);
+    }
+}
+
+/// Borrows a [`Receiver`](struct.Receiver.html) for the purpose of doing zero-copy deserialization of messages
+/// containing references.
+///
+/// An instance of this type may only be used to deserialize a single message before it is dropped because the
+/// [`Drop`](https://doc.rust-lang.org/std/ops/trait.Drop.html) implementation is what advances the ring buffer
+/// pointer.  Alternatively, [`ZeroCopyContext::commit`](struct.ZeroCopyContext.html#method.commit) may be used to
+/// advance the pointer explicitly, or [`ZeroCopyContext::abort`](struct.ZeroCopyContext.html#method.abort) to leave
+/// the message in the ring buffer to be received again, after which the instance may be used to receive another
+/// message.  Also, the borrowed [`Receiver`](struct.Receiver.html) may not be used directly while it is borrowed
+/// by a [`ZeroCopyContext`](struct.ZeroCopyContext.html).
+///
+/// References into a message remain valid, and the bytes they refer to remain unchanged, for as long as they are
+/// borrowed, regardless of how many messages senders write in the meantime.  This is because the read pointer is not
+/// advanced past the message until it is committed, and senders never write to the region between the read and
+/// write pointers (including when wrapping around to the beginning of the ring buffer), so they will wait for
+/// space rather than overwrite it.
+///
+/// Zero-copy deserialization never needs to fall back to copying: each message is stored contiguously, with
+/// senders writing a wrap marker and starting over at the beginning of the ring buffer rather than splitting a
+/// message across the end.
+///
+/// Use [`Receiver::zero_copy_context`](struct.Receiver.html#method.zero_copy_context) to create an instance.
+pub struct ZeroCopyContext<'a> {
+    receiver: &'a Receiver,
+    position: Option<u32>,
+}
+
+impl<'a> ZeroCopyContext<'a> {
+    /// Attempt to read a message without blocking.
+    ///
+    /// This will return `Ok(None)` if there are no messages immediately available.  It will return
+    /// `Err(`[`Error::AlreadyReceived`](enum.Error.html#variant.AlreadyReceived)`))` if this instance has already
+    /// been used to read a message.
+    pub fn try_recv<'b, T: Deserialize<'b>>(&'b mut self) -> Result<Option<T>> {
+        if self.position.is_some() {
+            Err(Error::AlreadyReceived)
+        } else {
+            Ok(
+                if let Some((value, position)) = self.receiver.try_recv_ref_0()? {
+                    self.position = Some(position);
+                    Some(value)
+                } else {
+                    None
+                },
+            )
+        }
+    }
+
+    /// Attempt to read a message sent using [`Sender::send_vectored`](struct.Sender.html#method.send_vectored),
+    /// blocking if necessary until one becomes available, and return slices borrowing each of its parts directly
+    /// from the ring buffer.
+    ///
+    /// This is equivalent to `recv::<Vec<&[u8]>>()`.
+    ///
+    /// This will return `Err(`[`Error::AlreadyReceived`](enum.Error.html#variant.AlreadyReceived)`))` if this
+    /// instance has already been used to read a message.
+    pub fn recv_vectored(&mut self) -> Result<Vec<&[u8]>> {
+        self.recv()
+    }
+
+    /// Attempt to read a message, blocking if necessary until one becomes available.
+    ///
+    /// This will return `Err(`[`Error::AlreadyReceived`](enum.Error.html#variant.AlreadyReceived)`))` if this
+    /// instance has already been used to read a message.
+    pub fn recv<'b, T: Deserialize<'b>>(&'b mut self) -> Result<T> {
+        if self.position.is_some() {
+            return Err(Error::AlreadyReceived);
+        }
+
+        let (value, position) = self.receiver.recv_0(Receiver::try_recv_ref_0)?;
+
+        self.position = Some(position);
+
+        Ok(value)
+    }
+
+    /// Attempt to read a message, blocking for up to the specified duration if necessary until one becomes
+    /// available.
+    ///
+    /// This will return `Err(`[`Error::AlreadyReceived`](enum.Error.html#variant.AlreadyReceived)`))` if this
+    /// instance has already been used to read a message.
+    pub fn recv_timeout<'b, T: Deserialize<'b>>(
+        &'b mut self,
+        timeout: Duration,
+    ) -> Result<Option<T>> {
+        if self.position.is_some() {
+            Err(Error::AlreadyReceived)
+        } else {
+            Ok(
+                if let Some((value, position)) = self
+                    .receiver
+                    .recv_timeout_0(Receiver::try_recv_ref_0, Some(timeout))?
+                {
+                    self.position = Some(position);
+                    Some(value)
+                } else {
+                    None
+                },
+            )
+        }
+    }
+
+    /// Attempt to read a message, blocking until the specified deadline if necessary until one becomes available.
+    ///
+    /// See [`Receiver::recv_deadline`](struct.Receiver.html#method.recv_deadline) for details.
+    ///
+    /// This will return `Err(`[`Error::AlreadyReceived`](enum.Error.html#variant.AlreadyReceived)`))` if this
+    /// instance has already been used to read a message.
+    pub fn recv_deadline<'b, T: Deserialize<'b>>(
+        &'b mut self,
+        deadline: Instant,
+    ) -> Result<Option<T>> {
+        if self.position.is_some() {
+            Err(Error::AlreadyReceived)
+        } else {
+            Ok(
+                if let Some((value, position)) = self
+                    .receiver
+                    .recv_deadline_0(Receiver::try_recv_ref_0, Some(deadline))?
+                {
+                    self.position = Some(position);
+                    Some(value)
+                } else {
+                    None
+                },
+            )
+        }
+    }
+
+    /// Consume the message most recently received using this instance (if any), advancing the read pointer past
+    /// it.
+    ///
+    /// This is what the [`Drop`](https://doc.rust-lang.org/std/ops/trait.Drop.html) implementation does
+    /// implicitly, but calling it explicitly allows errors to be observed and allows this instance to be used to
+    /// receive another message.
+    pub fn commit(&mut self) -> Result<()> {
+        if let Some(position) = self.position.take() {
+            self.receiver.seek(position)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Leave the message most recently received using this instance (if any) in the ring buffer, so that the next
+    /// read will receive it again.
+    ///
+    /// After this is called, the instance may be used to receive another message.
+    pub fn abort(&mut self) {
+        self.position = None;
+    }
+}
+
+impl<'a> Drop for ZeroCopyContext<'a> {
+    fn drop(&mut self) {
+        if let Some(position) = self.position.take() {
+            let _ = self.receiver.seek(position);
+        }
+    }
+}
+
+/// Borrows a [`Receiver`](struct.Receiver.html) for the purpose of doing zero-copy deserialization of several
+/// messages containing references, all of which may be borrowed at once.
+///
+/// Messages are received in order, each starting where the previous one ended, but the read pointer is only
+/// advanced past them when this is dropped or [`ZeroCopyBatch::commit`](#method.commit) is called, so references
+/// into any of them remain valid (and the bytes they refer to unchanged) until then.  This holds across wrap-around
+/// too: a batch follows senders back to the beginning of the ring buffer like a receiver does, and senders never
+/// write to the region between the read and write pointers, which includes every message in the batch.
+///
+/// The flip side is that the space occupied by a batch isn't freed until it is dropped, so a batch can hold at
+/// most a ring buffer's worth of messages.  Once it has received everything senders were able to write, they wait
+/// for space, and [`ZeroCopyBatch::recv`](#method.recv) would wait for them in turn, forever.  Use
+/// [`ZeroCopyBatch::try_recv`](#method.try_recv) or [`ZeroCopyBatch::recv_timeout`](#method.recv_timeout) to
+/// collect as many messages as are available instead, and [`ZeroCopyBatch::received`](#method.received) to find out
+/// how many that was.
+///
+/// Compressed messages can't be borrowed, so receiving one fails with
+/// [`Error::Compressed`](enum.Error.html#variant.Compressed) and leaves it in the ring buffer.  A message which
+/// fails its checksum is skipped, as with [`Receiver::recv`](struct.Receiver.html#method.recv).
+///
+/// Use [`Receiver::zero_copy_batch`](struct.Receiver.html#method.zero_copy_batch) to create an instance.
+pub struct ZeroCopyBatch<'a> {
+    receiver: &'a Receiver,
+    start: u32,
+    position: Cell<u32>,
+    received: Cell<usize>,
+}
+
+impl<'a> ZeroCopyBatch<'a> {
+    /// Attempt to read the next message without blocking.
+    ///
+    /// This will return `Ok(None)` if there are no messages immediately available.
+    pub fn try_recv<'b, T: Deserialize<'b>>(&'b self) -> Result<Option<T>> {
+        let position = &self.position;
+
+        Ok(
+            if let Some((bytes, end)) =
+                self.receiver
+                    .try_recv_raw_from_0(position.get(), |skipped| {
+                        position.set(skipped);
+                        Ok(())
+                    })?
+            {
+                let value = self.receiver.config.deserialize(bytes)?;
+                position.set(end);
+                self.received.set(self.received.get() + 1);
+                Some(value)
+            } else {
+                None
+            },
+        )
+    }
+
+    /// Attempt to read the next message, blocking if necessary until one becomes available.
+    ///
+    /// See the [type documentation](struct.ZeroCopyBatch.html) for why this may block forever once the batch
+    /// holds a ring buffer's worth of messages.
+    pub fn recv<'b, T: Deserialize<'b>>(&'b self) -> Result<T> {
+        loop {
+            if let Some(value) = self.recv_timeout_0(None)? {
+                break Ok(value);
+            }
+        }
+    }
+
+    /// Attempt to read the next message, blocking for up to the specified duration if necessary until one becomes
+    /// available.
+    pub fn recv_timeout<'b, T: Deserialize<'b>>(&'b self, timeout: Duration) -> Result<Option<T>> {
+        self.recv_timeout_0(Some(timeout))
+    }
+
+    fn recv_timeout_0<'b, T: Deserialize<'b>>(
+        &'b self,
+        timeout: Option<Duration>,
+    ) -> Result<Option<T>> {
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        loop {
+            if let Some(value) = self.try_recv()? {
+                return Ok(Some(value));
+            }
+
+            let now = Instant::now();
+            if deadline.map(|deadline| deadline > now).unwrap_or(true) {
+                self.receiver
+                    .wait_from_0(self.position.get(), deadline.map(|deadline| deadline - now))?;
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Returns the number of messages received using this batch so far.
+    pub fn received(&self) -> usize {
+        self.received.get()
+    }
+
+    /// Consume every message received using this batch, advancing the read pointer past them.
+    ///
+    /// This is what the [`Drop`](https://doc.rust-lang.org/std/ops/trait.Drop.html) implementation does
+    /// implicitly, but calling it explicitly allows errors to be observed.
+    pub fn commit(self) -> Result<()> {
+        self.commit_0()
+    }
+
+    /// Leave every message received using this batch in the ring buffer, so that the next read will receive them
+    /// again.
+    pub fn abort(self) {
+        self.position.set(self.start);
+    }
+
+    fn commit_0(&self) -> Result<()> {
+        let position = self.position.replace(self.start);
+        if position != self.start {
+            self.receiver.seek(position)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<'a> Drop for ZeroCopyBatch<'a> {
+    fn drop(&mut self) {
+        let _ = self.commit_0();
+    }
+}
+
+/// The serialized bytes of a message borrowed directly from the ring buffer, as returned by
+/// [`Receiver::try_recv_raw`](struct.Receiver.html#method.try_recv_raw) and
+/// [`Receiver::recv_bytes_ref`](struct.Receiver.html#method.recv_bytes_ref).
+///
+/// This dereferences to the bytes of the message as serialized by the sender (not including any framing), which
+/// may be deserialized in place using e.g. `bincode::deserialize` or any other codec.  The read pointer is advanced
+/// past the message when this is dropped, unless [`RawMessage::abort`](struct.RawMessage.html#method.abort) is
+/// called first.
+pub struct RawMessage<'a> {
+    receiver: &'a Receiver,
+    bytes: &'a [u8],
+    position: Option<u32>,
+}
+
+impl<'a> RawMessage<'a> {
+    /// Consume this message, advancing the read pointer past it.
+    ///
+    /// This is what the [`Drop`](https://doc.rust-lang.org/std/ops/trait.Drop.html) implementation does
+    /// implicitly, but calling it explicitly allows errors to be observed.
+    pub fn commit(mut self) -> Result<()> {
+        if let Some(position) = self.position.take() {
+            self.receiver.seek(position)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Leave this message in the ring buffer, so that the next read will receive it again.
+    pub fn abort(mut self) {
+        self.position = None;
+    }
+}
+
+impl<'a> Deref for RawMessage<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.bytes
+    }
+}
+
+impl<'a> Drop for RawMessage<'a> {
+    fn drop(&mut self) {
+        if let Some(position) = self.position.take() {
+            let _ = self.receiver.seek(position);
+        }
+    }
+}
+
+/// A message which may borrow directly from the ring buffer, as returned by
+/// [`Receiver::recv_ref`](struct.Receiver.html#method.recv_ref).
+///
+/// This dereferences to the message itself.  The read pointer is advanced past the message when this is dropped.
+pub struct RecvRef<'a, T> {
+    receiver: &'a Receiver,
+    value: T,
+    position: u32,
+}
+
+impl<'a, T> Deref for RecvRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<'a, T> Drop for RecvRef<'a, T> {
+    fn drop(&mut self) {
+        let _ = self.receiver.seek(self.position);
+    }
+}
+
+/// Represents the sending end of an inter-process channel.
+///
+/// The ring buffer keeps track of how many instances of this type exist (including clones), so that once every
+/// sender has been dropped and the receiver has drained any remaining messages, the receiver will get
+/// `Err(`[`Error::NoSenders`](enum.Error.html#variant.NoSenders)`)` rather than waiting forever.  Note that a
+/// sender in a process which exits without running destructors (e.g. due to a crash or `std::process::exit`)
+/// will never be counted as disconnected.
+///
+/// A sender is counted on behalf of the process which constructed it, so a copy inherited by a child process via
+/// `fork` is not counted separately, and dropping it there has no effect on the count.  The original must
+/// therefore outlive the child's use of the copy, or else the receiver may report
+/// [`Error::NoSenders`](enum.Error.html#variant.NoSenders) while the child is still sending.  The
+/// [`fork`](fn.fork.html) function takes care of this for senders captured by the function it runs, keeping the
+/// parent's copies alive until the child exits.
+///
+/// Within a process, [`Sender::clone_count`](struct.Sender.html#method.clone_count) reports how many instances
+/// share a common origin via `clone`, which may help diagnose leaked senders keeping a channel alive.
+pub struct Sender {
+    buffer: SharedRingBuffer,
+    buffer_id: u64,
+    // ID of the process which counted this sender in `Header::sender_count` (see `Drop`).
+    pid: u32,
+    clones: Arc<Clones>,
+    config: Config,
+    #[cfg(feature = "compression")]
+    compression: Compression,
+}
+
+/// Tracks the number of live clones of a [`Sender`](struct.Sender.html), along with the optional limit on that
+/// number.
+struct Clones {
+    count: AtomicUsize,
+    max: usize,
+}
+
+impl Sender {
+    /// Constructs a [`Sender`](struct.Sender.html) from the specified
+    /// [`SharedRingBuffer`](struct.SharedRingBuffer.html)
+    pub fn new(buffer: SharedRingBuffer) -> Self {
+        Self::with_max_clones(buffer, usize::MAX)
+    }
+
+    /// Constructs a [`Sender`](struct.Sender.html) from the specified
+    /// [`SharedRingBuffer`](struct.SharedRingBuffer.html) which, together with its clones, may have at most
+    /// `max_clones` live instances at a time.
+    ///
+    /// Only [`Sender::try_clone`](struct.Sender.html#method.try_clone) enforces the limit, returning
+    /// `Err(`[`Error::TooManySenders`](enum.Error.html#variant.TooManySenders)`)` once it is reached.
+    /// `Sender::clone` never fails, since generic code may call it implicitly, so clones made that way count toward
+    /// the limit but may exceed it.  Senders constructed separately (e.g. in other processes) do not count toward
+    /// the limit.
+    pub fn with_max_clones(buffer: SharedRingBuffer, max_clones: usize) -> Self {
+        Self::new_0(
+            buffer,
+            Arc::new(Clones {
+                count: AtomicUsize::new(1),
+                max: max_clones,
+            }),
+        )
+    }
+
+    fn new_0(buffer: SharedRingBuffer, clones: Arc<Clones>) -> Self {
+        let header = buffer.0.buffer().header();
+
+        // Increment the count before marking a sender as connected so the receiver never sees the latter without
+        // the former (see `SharedRingBuffer::senders_disconnected`).
+        header.sender_count.fetch_add(1, Relaxed);
+        header.sender_connected.store(1, Release);
+
+        stamp(&header.sender_heartbeat);
+
+        Self {
+            buffer_id: buffer.buffer_id(),
+            buffer,
+            pid: std::process::id(),
+            clones,
+            config: Config::default(),
+            #[cfg(feature = "compression")]
+            compression: Compression::None,
+        }
+    }
+
+    /// Serialize messages sent using this sender (and any clones of it made afterward) using the specified bincode
+    /// configuration, which must match that of the [`Receiver`](struct.Receiver.html) (see
+    /// [`Receiver::with_config`](struct.Receiver.html#method.with_config)).
+    ///
+    /// The configuration applies to every method which serializes a message, including the length prefixes written
+    /// by [`Sender::send_vectored`](struct.Sender.html#method.send_vectored); methods which send raw bytes send
+    /// them as is.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Compress messages sent using this sender (and any clones of it made afterward) as specified.  Only available
+    /// with the `compression` feature enabled.
+    ///
+    /// This trades CPU time for effective ring buffer capacity, which pays off for large messages with lots of
+    /// repetition.  A message which wouldn't get smaller is stored uncompressed, so compression never inflates a
+    /// message, and receivers detect compressed messages automatically.  However, compressed messages can't be
+    /// received using zero-copy methods (see [`Error::Compressed`](enum.Error.html#variant.Compressed)).
+    ///
+    /// Compression applies to messages sent using [`Sender::send`](struct.Sender.html#method.send),
+    /// [`Sender::send_timeout`](struct.Sender.html#method.send_timeout),
+    /// [`Sender::try_send`](struct.Sender.html#method.try_send),
+    /// [`Sender::send_when_empty`](struct.Sender.html#method.send_when_empty), and
+    /// [`Sender::send_reporting`](struct.Sender.html#method.send_reporting); other methods always send messages
+    /// as is.  The size checked against the ring buffer capacity is that of the message as stored, i.e. after
+    /// compression.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Returns the number of live instances sharing this sender's origin, i.e. the sender returned by
+    /// [`Sender::new`](struct.Sender.html#method.new) or
+    /// [`Sender::with_max_clones`](struct.Sender.html#method.with_max_clones) plus any clones of it (including
+    /// this one).
+    pub fn clone_count(&self) -> usize {
+        self.clones.count.load(Relaxed)
+    }
+
+    /// Attempt to clone this sender, returning
+    /// `Err(`[`Error::TooManySenders`](enum.Error.html#variant.TooManySenders)`)` if doing so would exceed the
+    /// limit passed to [`Sender::with_max_clones`](struct.Sender.html#method.with_max_clones).
+    pub fn try_clone(&self) -> Result<Self> {
+        let max = self.clones.max;
+
+        self.clones
+            .count
+            .fetch_update(Relaxed, Relaxed, |count| {
+                if count < max {
+                    Some(count + 1)
+                } else {
+                    None
+                }
+            })
+            .map_err(|_| Error::TooManySenders)?;
+
+        Ok(self.clone_0())
+    }
+
+    /// Construct a clone of this sender, which must already have been counted in `Clones::count`.
+    fn clone_0(&self) -> Self {
+        let mut sender = Self::new_0(self.buffer.clone(), self.clones.clone());
+
+        sender.config = self.config;
+
+        #[cfg(feature = "compression")]
+        {
+            sender.compression = self.compression;
+        }
+
+        sender
+    }
+
+    /// Returns `true` if a [`Receiver`](struct.Receiver.html) currently exists for this ring buffer, or `false` if
+    /// none has been constructed yet or it has been dropped.
+    ///
+    /// This is a cheap, non-blocking check which does not acquire the ring buffer lock, so the result may be stale
+    /// by the time the caller acts on it.  It is intended as a hint, e.g. for skipping the serialization of an
+    /// expensive message when nobody is listening.
+    pub fn is_receiver_alive(&self) -> bool {
+        self.buffer.0.buffer().header().receiver_alive.load(Acquire) != 0
+    }
+
+    /// Record that this sender is still alive, for the benefit of a receiver calling
+    /// [`Receiver::peer_stale`](struct.Receiver.html#method.peer_stale).
+    ///
+    /// This is done automatically when the sender is constructed.  All senders for a given ring buffer share a
+    /// single heartbeat, so it is sufficient for any one of them to call this periodically.
+    pub fn heartbeat(&self) {
+        stamp(&self.buffer.0.buffer().header().sender_heartbeat);
+    }
+
+    /// Returns a [`ShutdownToken`](struct.ShutdownToken.html) which may be used to interrupt blocked sends to this
+    /// sender's ring buffer, as well as the receiver.
+    pub fn shutdown_token(&self) -> ShutdownToken {
+        ShutdownToken::new(self.buffer.clone())
+    }
+
+    /// Shut down this sender's ring buffer, waking any blocked senders and receiver.
+    ///
+    /// This is a shorthand for `shutdown_token().shutdown()`; see
+    /// [`ShutdownToken::shutdown`](struct.ShutdownToken.html#method.shutdown) for details.
+    pub fn shutdown(&self) -> Result<()> {
+        self.shutdown_token().shutdown()
+    }
+
+    /// Returns `true` if the [`Receiver`](struct.Receiver.html) for this ring buffer has not recorded a heartbeat
+    /// (see [`Receiver::heartbeat`](struct.Receiver.html#method.heartbeat)) within `max_age`, e.g. because it has
+    /// crashed.
+    ///
+    /// This complements [`Sender::is_receiver_alive`](struct.Sender.html#method.is_receiver_alive), which detects
+    /// a receiver which was dropped but not one in a process which was killed without running destructors.
+    pub fn peer_stale(&self, max_age: Duration) -> bool {
+        stale(&self.buffer.0.buffer().header().receiver_heartbeat, max_age)
+    }
+
+    /// Returns the size of the specified message once serialized, not including framing, e.g. to decide whether
+    /// to compress or split it before sending.
+    ///
+    /// If the serialized size can't be represented as a `u32`, this method will return
+    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
+    pub fn serialized_size(&self, value: &impl Serialize) -> Result<u32> {
+        u32::try_from(self.config.serialized_size(value)?).map_err(|_| Error::MessageTooLarge)
+    }
+
+    /// Returns the largest serialized size of a message this sender will accept, i.e. the ring buffer capacity.
+    ///
+    /// A message of at most this size (as reported by
+    /// [`Sender::serialized_size`](struct.Sender.html#method.serialized_size)) is never rejected with
+    /// [`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge), since the framing overhead (see
+    /// [`MESSAGE_OVERHEAD`](constant.MESSAGE_OVERHEAD.html)) and the space reserved for a wrap marker are accounted
+    /// for separately; any larger message is always rejected.
+    pub fn max_message_size(&self) -> u32 {
+        self.buffer.size_in_bytes()
+    }
+
+    /// Returns a snapshot of the cumulative statistics for this sender's ring buffer, covering messages sent by
+    /// every sender; see [`SharedRingBuffer::stats`](struct.SharedRingBuffer.html#method.stats).
+    pub fn stats(&self) -> Stats {
+        self.buffer.stats()
+    }
+
+    /// Send the specified message, waiting for sufficient contiguous space to become available in the ring buffer
+    /// if necessary.
+    ///
+    /// The serialized size of the message must be greater than zero, unless the ring buffer allows zero-sized
+    /// messages (see
+    /// [`SharedRingBufferBuilder::allow_zero_sized`](struct.SharedRingBufferBuilder.html#method.allow_zero_sized)),
+    /// or else this method will return `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))`.
+    /// If the serialized size is greater than the ring buffer capacity, this method will return
+    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
+    pub fn send(&self, value: &impl Serialize) -> Result<()> {
+        self.send_timeout_0(value, false, None).map(drop)
+    }
+
+    /// Send the specified message prefixed by its
+    /// [`Schema::SCHEMA_HASH`](trait.Schema.html#associatedconstant.SCHEMA_HASH), waiting for sufficient contiguous
+    /// space to become available in the ring buffer if necessary.
+    ///
+    /// The message should be received using [`Receiver::recv_checked`](struct.Receiver.html#method.recv_checked)
+    /// (or similar), which will verify the fingerprint before deserializing it.  The fingerprint adds eight bytes
+    /// to the serialized size of the message (or up to nine using variable-length integers; see
+    /// [`Config`](struct.Config.html)).
+    pub fn send_checked<T: Schema + Serialize>(&self, value: &T) -> Result<()> {
+        self.send(&(T::SCHEMA_HASH, value))
+    }
+
+    /// Send the specified message prefixed by its [`Tagged::TAG`](trait.Tagged.html#associatedconstant.TAG),
+    /// waiting for sufficient contiguous space to become available in the ring buffer if necessary.
+    ///
+    /// The message should be received using [`Receiver::recv_tagged`](struct.Receiver.html#method.recv_tagged)
+    /// (or similar).  The tag adds two bytes to the serialized size of the message (or between one and three using
+    /// variable-length integers; see [`Config`](struct.Config.html)).
+    pub fn send_tagged<T: Tagged + Serialize>(&self, value: &T) -> Result<()> {
+        self.send(&(T::TAG, value))
+    }
+
+    /// Send the specified message, waiting for sufficient contiguous space to become available in the ring buffer
+    /// if necessary, and report whether any waiting was required.
+    ///
+    /// This behaves exactly like [`Sender::send`](struct.Sender.html#method.send), but returns a
+    /// [`SendOutcome`](enum.SendOutcome.html) indicating whether the message was written immediately or only after
+    /// blocking (and for how long), which is useful for tuning the ring buffer size.
+    pub fn send_reporting(&self, value: &impl Serialize) -> Result<SendOutcome> {
+        self.send_timeout_0(value, false, None)?
+            .ok_or_else(|| Error::Runtime("send without a timeout returned early".into()))
+    }
+
+    /// Send the specified message, waiting for sufficient contiguous space to become available in the ring buffer
+    /// if necessary, but only up to the specified timeout.
+    ///
+    /// This will return `Ok(true)` if the message was sent, or `Ok(false)` if it timed out while waiting for
+    /// space, in which case nothing the receiver can observe has changed: the message was not written, and the most
+    /// that may have been written is a wrap marker (see [`Sender::try_send`](struct.Sender.html#method.try_send)).
+    /// The timeout is measured from when this method is called, regardless of how many times the sender is woken
+    /// (spuriously or otherwise) before space becomes available.  The timeout also bounds the time spent acquiring
+    /// the ring buffer lock, which is normally brief but may be held for longer by a slow or misbehaving sender; if
+    /// it can't be acquired in time, this method will return
+    /// `Err(`[`Error::LockTimeout`](enum.Error.html#variant.LockTimeout)`))`.
+    ///
+    /// The serialized size of the message must be greater than zero, unless the ring buffer allows zero-sized
+    /// messages (see
+    /// [`SharedRingBufferBuilder::allow_zero_sized`](struct.SharedRingBufferBuilder.html#method.allow_zero_sized)),
+    /// or else this method will return `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))`.
+    /// If the serialized size is greater than the ring buffer capacity, this method will return
+    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
+    pub fn send_timeout(&self, value: &impl Serialize, timeout: Duration) -> Result<bool> {
+        self.send_timeout_0(value, false, Some(timeout))
+            .map(|outcome| outcome.is_some())
+    }
+
+    /// Send the specified message if it can be done without blocking.
+    ///
+    /// This is equivalent to [`Sender::send_timeout`](struct.Sender.html#method.send_timeout) with a timeout of
+    /// zero: it will return `Ok(false)` if there is not enough space in the ring buffer, or
+    /// `Err(`[`Error::LockTimeout`](enum.Error.html#variant.LockTimeout)`))` if another sender holds the lock.
+    /// Either way, it never waits, so it is suitable for e.g. real-time threads which would rather drop a message
+    /// than stall.  If the message has to wrap around to the beginning of the ring buffer, the wrap marker is
+    /// written (and left in place) even if the message itself then doesn't fit.
+    ///
+    /// As with [`Sender::send`](struct.Sender.html#method.send), a message of zero serialized size results in
+    /// `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))` and one larger than the ring
+    /// buffer capacity results in `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`,
+    /// regardless of how much space is available.
+    pub fn try_send(&self, value: &impl Serialize) -> Result<bool> {
+        self.send_timeout(value, Duration::from_secs(0))
+    }
+
+    /// Send the specified message, waiting for the ring buffer to become completely empty first.
+    ///
+    /// This method is appropriate for sending time-sensitive messages where buffering would introduce undesirable
+    /// latency.
+    ///
+    /// The serialized size of the message must be greater than zero, unless the ring buffer allows zero-sized
+    /// messages (see
+    /// [`SharedRingBufferBuilder::allow_zero_sized`](struct.SharedRingBufferBuilder.html#method.allow_zero_sized)),
+    /// or else this method will return `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))`.
+    /// If the serialized size is greater than the ring buffer capacity, this method will return
+    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
+    pub fn send_when_empty(&self, value: &impl Serialize) -> Result<()> {
+        self.send_timeout_0(value, true, None).map(drop)
+    }
+
+    /// Send the specified slices as a single message, waiting for sufficient contiguous space to become available
+    /// in the ring buffer if necessary.
+    ///
+    /// The parts are copied directly into the ring buffer, preceded by their lengths, without first being gathered
+    /// into an intermediate buffer.  The message is encoded exactly as bincode would encode a `Vec<&[u8]>`, so it
+    /// may be received using [`ZeroCopyContext::recv_vectored`](struct.ZeroCopyContext.html#method.recv_vectored)
+    /// (or as any type which deserializes from a sequence of byte sequences, e.g. `Vec<Vec<u8>>`).
+    ///
+    /// If the total size of the message is greater than the ring buffer capacity, this method will return
+    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
+    pub fn send_vectored(&self, parts: &[&[u8]]) -> Result<()> {
+        // Each part is prefixed with its length, and the whole sequence with the number of parts, each as a u64.
+        let size = parts.iter().try_fold(
+            self.serialized_size(&(parts.len() as u64))?,
+            |size, part| {
+                let len = u32::try_from(part.len()).map_err(|_| Error::MessageTooLarge)?;
+                size.checked_add(self.serialized_size(&(part.len() as u64))?)
+                    .and_then(|size| size.checked_add(len))
+                    .ok_or(Error::MessageTooLarge)
+            },
+        )?;
+
+        self.write_timeout_0(size, UNCOMPRESSED, false, None, |mut slice| {
+            self.config
+                .serialize_into(&mut slice, &(parts.len() as u64))?;
+            for part in parts {
+                self.config
+                    .serialize_into(&mut slice, &(part.len() as u64))?;
+                slice.write_all(part)?;
+            }
+            Ok(())
+        })
+        .map(drop)
+    }
+
+    /// Send all of the specified messages or none of them, waiting for sufficient contiguous space for all of
+    /// them to become available in the ring buffer if necessary.
+    ///
+    /// The messages are written contiguously and published at once, so the receiver will never observe only some
+    /// of them, and messages from other senders will not be interleaved with them.
+    ///
+    /// The serialized size of each message must be greater than zero or else this method will return
+    /// `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))`.  If the total size of the
+    /// messages (including per-message overhead) is greater than the ring buffer capacity, this method will return
+    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))` without sending anything.
+    pub fn send_all_or_none(&self, values: &[impl Serialize]) -> Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        let sizes = self.serialized_sizes(values)?;
+
+        let frames_size = sizes
+            .iter()
+            .try_fold(0_u32, |total, &size| total.checked_add(frame_size(size)?))
+            .ok_or(Error::MessageTooLarge)?;
+
+        let frame_count = u32::try_from(values.len()).map_err(|_| Error::MessageTooLarge)?;
+
+        self.write_frames_timeout_0(frames_size, frame_count, false, None, |frames| {
+            write_values(frames, self.config, values, &sizes)?;
+            Ok(frames_size)
+        })
+        .map(drop)
+    }
+
+    /// Send each of the specified messages in order, waiting for sufficient contiguous space to become available in
+    /// the ring buffer as necessary.
+    ///
+    /// This is equivalent to sending the messages one at a time, but much cheaper for large numbers of small
+    /// messages: they are written in groups, acquiring the ring buffer lock and notifying the receiver once per
+    /// group rather than once per message.  Each group holds up to half the ring buffer capacity, so the receiver
+    /// can consume one group while the next is being written.  Unlike
+    /// [`Sender::send_all_or_none`](struct.Sender.html#method.send_all_or_none), the receiver may observe some
+    /// groups before others have been written, and messages from other senders may be interleaved between groups,
+    /// but it never observes a partially written message.
+    ///
+    /// Every message is checked before any are sent: if the serialized size of any of them is zero, this method
+    /// will return `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))`, and if it is
+    /// greater than the ring buffer capacity,
+    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
+    pub fn send_all(&self, values: &[impl Serialize]) -> Result<()> {
+        let sizes = self.serialized_sizes(values)?;
+
+        let capacity = self.buffer.size_in_bytes();
+
+        if sizes.iter().any(|&size| size > capacity) {
+            return Err(Error::MessageTooLarge);
+        }
+
+        let limit = capacity / 2;
+        let max_messages = self.buffer.0.buffer().header().max_messages.load(Relaxed) as usize;
+
+        let mut start = 0;
+        while start < values.len() {
+            // The first message always goes in the group, even if it is bigger than the limit by itself.
+            let mut group_size = frame_size(sizes[start]).ok_or(Error::MessageTooLarge)?;
+            let mut end = start + 1;
+            while end < values.len() && (max_messages == 0 || end - start < max_messages) {
+                match frame_size(sizes[end]).and_then(|size| group_size.checked_add(size)) {
+                    Some(size) if size <= limit => {
+                        group_size = size;
+                        end += 1;
+                    }
+                    _ => break,
+                }
+            }
+
+            self.write_frames_timeout_0(group_size, (end - start) as u32, false, None, |frames| {
+                write_values(frames, self.config, &values[start..end], &sizes[start..end])?;
+                Ok(group_size)
+            })?;
+
+            start = end;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the serialized size of each of `values`, or an error if any of them is zero-sized (and that isn't
+    /// allowed) or too large to represent.
+    fn serialized_sizes(&self, values: &[impl Serialize]) -> Result<Vec<u32>> {
+        let zero_sized_allowed = self.zero_sized_allowed();
+
+        values
+            .iter()
+            .map(|value| match self.config.serialized_size(value)? {
+                0 if !zero_sized_allowed => Err(Error::ZeroSizedMessage),
+                size => u32::try_from(size).map_err(|_| Error::MessageTooLarge),
+            })
+            .collect()
+    }
+
+    /// Send a single message whose contents are written directly into the ring buffer by `write_message`, waiting
+    /// for sufficient contiguous space to become available if necessary.
+    ///
+    /// This avoids materializing the whole message before sending it, e.g. by passing the writer to
+    /// `bincode::serialize_into` or `serde_json::to_writer`.  Since the final size is not known in advance,
+    /// `max_size` bytes are reserved up front (at most
+    /// [`SharedRingBuffer::size_in_bytes`](struct.SharedRingBuffer.html#method.size_in_bytes) minus a few bytes of
+    /// framing overhead), and only the bytes actually written are published.  Other senders are blocked until
+    /// `write_message` returns, so it should not do anything slow besides writing.
+    ///
+    /// If `write_message` writes nothing, this method will return
+    /// `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))`.  If it attempts to write
+    /// more than `max_size` bytes, or `max_size` is greater than the ring buffer capacity, this method will return
+    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.  In either case, or if
+    /// `write_message` itself fails, nothing is sent.
+    pub fn send_writer(
+        &self,
+        max_size: u32,
+        write_message: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+    ) -> Result<()> {
+        if max_size == 0 && !self.zero_sized_allowed() {
+            return Err(Error::ZeroSizedMessage);
+        }
+
+        // Reserve space for the full framing overhead, even though a zero-sized message won't need all of it.
+        let reserved = max_size
+            .checked_add(MESSAGE_OVERHEAD)
+            .ok_or(Error::MessageTooLarge)?;
+
+        self.write_frames_timeout_0(reserved, 1, false, None, |frame| {
+            let mut body = &mut frame[MESSAGE_OVERHEAD as usize..];
+            let available = body.len();
+
+            write_message(&mut body).map_err(|e| {
+                if e.kind() == io::ErrorKind::WriteZero {
+                    Error::MessageTooLarge
+                } else {
+                    Error::Io(e)
+                }
+            })?;
+
+            let size = (available - body.len()) as u32;
+
+            if size == 0 && !self.zero_sized_allowed() {
+                return Err(Error::ZeroSizedMessage);
+            }
+
+            write_frame_header(frame, size, self.config.fingerprint())?;
+
+            frame_size(size).ok_or(Error::MessageTooLarge)
+        })
+        .map(drop)
+    }
+
+    /// Returns `true` if the ring buffer was created with
+    /// [`SharedRingBufferBuilder::allow_zero_sized`](struct.SharedRingBufferBuilder.html#method.allow_zero_sized).
+    fn zero_sized_allowed(&self) -> bool {
+        self.buffer.0.buffer().header().options.load(Relaxed) & OPTION_ZERO_SIZED != 0
+    }
+
+    fn send_timeout_0(
+        &self,
+        value: &impl Serialize,
+        wait_until_empty: bool,
+        timeout: Option<Duration>,
+    ) -> Result<Option<SendOutcome>> {
+        #[cfg(feature = "compression")]
+        {
+            if self.compression != Compression::None {
+                let serialized = self.config.serialize(value)?;
+                let (marker, message) = compression::compress(self.compression, &serialized)
+                    .unwrap_or((UNCOMPRESSED, serialized));
+                let size = u32::try_from(message.len()).map_err(|_| Error::MessageTooLarge)?;
+
+                return self.write_timeout_0(size, marker, wait_until_empty, timeout, |slice| {
+                    slice.copy_from_slice(&message);
+                    Ok(())
+                });
+            }
+        }
+
+        let size = self.serialized_size(value)?;
+
+        self.write_timeout_0(size, UNCOMPRESSED, wait_until_empty, timeout, |slice| {
+            self.config.serialize_into(slice, value)
+        })
+    }
+
+    /// Send a message which has already been serialized, waiting for sufficient contiguous space to become
+    /// available in the ring buffer if necessary.
+    ///
+    /// `bytes` is written to the ring buffer as is, framed only by its length, so it may be the bincode
+    /// serialization of some message type the receiver expects (e.g. as obtained from
+    /// [`Receiver::recv_bytes`](struct.Receiver.html#method.recv_bytes) when relaying messages between ring
+    /// buffers) or the output of any other codec, in which case the receiver should use
+    /// [`Receiver::recv_bytes`](struct.Receiver.html#method.recv_bytes) or
+    /// [`Receiver::recv_bytes_ref`](struct.Receiver.html#method.recv_bytes_ref) and decode it likewise.
+    ///
+    /// If `bytes` is empty, this method will return
+    /// `Err(`[`Error::ZeroSizedMessage`](enum.Error.html#variant.ZeroSizedMessage)`))` unless zero-sized messages are
+    /// enabled.  If it is larger than the ring buffer capacity, this method will return
+    /// `Err(`[`Error::MessageTooLarge`](enum.Error.html#variant.MessageTooLarge)`))`.
+    pub fn send_bytes(&self, bytes: &[u8]) -> Result<()> {
+        self.send_serialized_timeout_0(bytes, None).map(drop)
+    }
+
+    /// Send a message which has already been serialized, e.g. by an
+    /// [`AsyncSender`](struct.AsyncSender.html).
+    fn send_serialized_timeout_0(
+        &self,
+        bytes: &[u8],
+        timeout: Option<Duration>,
+    ) -> Result<Option<SendOutcome>> {
+        if bytes.len() > u32::MAX as usize {
+            return Err(Error::MessageTooLarge);
+        }
+
+        self.write_timeout_0(bytes.len() as u32, UNCOMPRESSED, false, timeout, |slice| {
+            slice.copy_from_slice(bytes);
+            Ok(())
+        })
+    }
+
+    /// Wait for `size` bytes of contiguous space to become available and then fill it using `write`, marking the
+    /// message as compressed according to `marker`.
+    fn write_timeout_0(
+        &self,
+        size: u32,
+        marker: u8,
+        wait_until_empty: bool,
+        timeout: Option<Duration>,
+        write_message: impl FnOnce(&mut [u8]) -> Result<()>,
+    ) -> Result<Option<SendOutcome>> {
+        if size == 0 && !self.zero_sized_allowed() {
+            return Err(Error::ZeroSizedMessage);
+        }
+
+        let frame_size = frame_size(size).ok_or(Error::MessageTooLarge)?;
+
+        self.write_frames_timeout_0(frame_size, 1, wait_until_empty, timeout, |frames| {
+            write_frame(frames, size, self.config.fingerprint(), write_message)?;
+            mark_compression(frames, marker);
+            Ok(frame_size)
+        })
+    }
+
+    /// Write frames to this sender's ring buffer; see `SharedRingBuffer::write_frames_timeout_0`.
+    fn write_frames_timeout_0(
+        &self,
+        frames_size: u32,
+        frame_count: u32,
+        wait_until_empty: bool,
+        timeout: Option<Duration>,
+        write_frames: impl FnOnce(&mut [u8]) -> Result<u32>,
+    ) -> Result<Option<SendOutcome>> {
+        debug_assert_eq!(
+            self.buffer_id,
+            self.buffer.buffer_id(),
+            "sender used with wrong ring buffer"
+        );
+
+        self.buffer.write_frames_timeout_0(
+            frames_size,
+            frame_count,
+            wait_until_empty,
+            timeout,
+            write_frames,
+        )
+    }
+}
+
+/// Handle used to interrupt [`Sender`](struct.Sender.html)s blocked waiting for space in a ring buffer, e.g. during
+/// process teardown when the receiver may have stalled.
+///
+/// Shutdown is permanent and applies to every sender for the ring buffer, including those in other processes.
+#[derive(Clone)]
+pub struct ShutdownToken(SharedRingBuffer);
+
+impl ShutdownToken {
+    /// Constructs a [`ShutdownToken`](struct.ShutdownToken.html) for the specified
+    /// [`SharedRingBuffer`](struct.SharedRingBuffer.html)
+    pub fn new(buffer: SharedRingBuffer) -> Self {
+        Self(buffer)
+    }
+
+    /// Mark the ring buffer as shut down and wake any blocked senders and receiver.
+    ///
+    /// From then on, any send which would otherwise wait for space in the ring buffer (including those already
+    /// waiting) will instead return `Err(`[`Error::Interrupted`](enum.Error.html#variant.Interrupted)`)`.  Sends
+    /// for which space is immediately available are unaffected.  The receiver still receives any messages already
+    /// in the ring buffer, but once it is empty, every receive (including one already waiting) returns
+    /// `Err(`[`Error::Interrupted`](enum.Error.html#variant.Interrupted)`)` rather than blocking, and iterators
+    /// such as [`Receiver::byte_iter`](struct.Receiver.html#method.byte_iter) end.  This provides a clean
+    /// cancellation signal without resorting to sentinel messages.
+    pub fn shutdown(&self) -> Result<()> {
+        let buffer = self.0 .0.buffer();
+
+        // Set the flag while holding the lock so neither a sender nor the receiver can miss it between checking and
+        // waiting.
+        let mut lock = buffer.lock()?;
+        buffer.header().shutdown.store(1, Release);
+        lock.notify_all()?;
+        drop(lock);
+
+        notify_data_ready(buffer);
+
+        Ok(())
+    }
+
+    /// Returns `true` if [`ShutdownToken::shutdown`](struct.ShutdownToken.html#method.shutdown) has been called for
+    /// this ring buffer.
+    pub fn is_shutdown(&self) -> bool {
+        self.0.shut_down()
+    }
+}
+
+/// Guard which counts a [`Sender`](struct.Sender.html) in `Header::waiting_senders` for as long as it exists.
+struct WaitingSender<'a>(&'a Header);
+
+impl<'a> WaitingSender<'a> {
+    fn new(header: &'a Header) -> Self {
+        header.waiting_senders.fetch_add(1, SeqCst);
+        Self(header)
+    }
+}
+
+impl Drop for WaitingSender<'_> {
+    fn drop(&mut self) {
+        self.0.waiting_senders.fetch_sub(1, SeqCst);
+    }
+}
+
+/// Guard which counts a receiver in `Header::waiting_receivers` while it waits on the condition variable.
+///
+/// An [`SpscSender`](struct.SpscSender.html) publishes messages without holding the lock, so it relies on this to
+/// know when it must acquire the lock to notify a receiver.  The receiver registers before acquiring the lock and
+/// checking the write pointer, so either it sees the new write pointer or the sender sees the registration.
+struct WaitingReceiver<'a>(&'a Header);
+
+impl<'a> WaitingReceiver<'a> {
+    fn new(header: &'a Header) -> Self {
+        header.waiting_receivers.fetch_add(1, SeqCst);
+        Self(header)
+    }
+}
+
+impl Drop for WaitingReceiver<'_> {
+    fn drop(&mut self) {
+        self.0.waiting_receivers.fetch_sub(1, SeqCst);
+    }
+}
+
+impl Clone for Sender {
+    /// Clone this sender.
+    ///
+    /// This never fails or panics, even if it exceeds the limit passed to
+    /// [`Sender::with_max_clones`](struct.Sender.html#method.with_max_clones); use
+    /// [`Sender::try_clone`](struct.Sender.html#method.try_clone) to respect that limit.
+    fn clone(&self) -> Self {
+        self.clones.count.fetch_add(1, Relaxed);
+        self.clone_0()
+    }
+}
+
+impl fmt::Debug for Sender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("buffer", &self.buffer)
+            .field("clone_count", &self.clone_count())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        self.clones.count.fetch_sub(1, Relaxed);
+
+        let buffer = self.buffer.0.buffer();
+
+        // Decrement the count while holding the lock (if possible) so a receiver can't miss the notification
+        // between checking the count and waiting.
+        let lock = buffer.lock();
+
+        // A copy inherited via `fork` was never counted, and the original will be uncounted by the parent.  If the
+        // ring buffer was recreated in place (e.g. by a restarted receiver), the count belongs to the new one, which
+        // never counted this sender.
+        if std::process::id() == self.pid
+            && buffer.header().buffer_id.load(Relaxed) == self.buffer_id
+        {
+            buffer.header().sender_count.fetch_sub(1, Release);
+        }
+
+        if let Ok(mut lock) = lock {
+            let _ = lock.notify_all();
+        }
+
+        notify_data_ready(buffer);
+    }
+}
+
+/// Count the frames between `read` and `write` in `slice` (the whole shared memory file), following any wrap
+/// marker.
+fn count_frames(slice: &[u8], mut read: u32, write: u32) -> Result<u32> {
+    let mut count = 0;
+    while read != write {
+        let start = read + 4;
+        let size = bincode::deserialize::<u32>(
+            slice
+                .get(read as usize..start as usize)
+                .ok_or(Error::CorruptBuffer)?,
+        )?;
+        if size == EMPTY_MESSAGE {
+            read = start;
+        } else if size > 0 {
+            read = start + size;
+        } else if write < read {
+            read = BEGINNING;
+            continue;
+        } else {
+            return Err(Error::CorruptBuffer);
+        }
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Returns the size of a frame holding a message of the specified size, including the size word and configuration
+/// fingerprint (if any), or `None` if it would overflow.
+fn frame_size(message_size: u32) -> Option<u32> {
+    if message_size == 0 {
+        Some(4)
+    } else {
+        message_size.checked_add(MESSAGE_OVERHEAD)
+    }
+}
+
+/// Write a frame holding a message of size `message_size` to `frame`, using `write_message` to write the message
+/// itself and `fingerprint` to identify the configuration it was serialized with.
+fn write_frame(
+    frame: &mut [u8],
+    message_size: u32,
+    fingerprint: u8,
+    write_message: impl FnOnce(&mut [u8]) -> Result<()>,
+) -> Result<()> {
+    if message_size == 0 {
+        return write_frame_header(frame, 0, fingerprint);
+    }
+
+    write_message(&mut frame[MESSAGE_OVERHEAD as usize..])?;
+
+    write_frame_header(frame, message_size, fingerprint)
+}
+
+/// Write a frame for each of `values` to `frames`, which must be exactly large enough, given that their serialized
+/// sizes using `config` are `sizes`.
+fn write_values(
+    mut frames: &mut [u8],
+    config: Config,
+    values: &[impl Serialize],
+    sizes: &[u32],
+) -> Result<()> {
+    for (value, &size) in values.iter().zip(sizes) {
+        let (frame, rest) =
+            frames.split_at_mut(frame_size(size).ok_or(Error::MessageTooLarge)? as usize);
+        write_frame(frame, size, config.fingerprint(), |slice| {
+            config.serialize_into(slice, value)
+        })?;
+        frames = rest;
+    }
+
+    Ok(())
+}
+
+/// Write the size word (and configuration fingerprint and checksum, if any) for a message of size `message_size`
+/// to the beginning of `frame`.
+///
+/// The message itself must already have been written to the remainder of `frame`, so that it can be checksummed.
+/// A zero-sized message is represented by `EMPTY_MESSAGE` alone, with no fingerprint or checksum.
+#[cfg_attr(not(feature = "fingerprint"), allow(unused_variables))]
+fn write_frame_header(frame: &mut [u8], message_size: u32, fingerprint: u8) -> Result<()> {
+    if message_size == 0 {
+        bincode::serialize_into(&mut frame[..4], &EMPTY_MESSAGE)?;
+        return Ok(());
+    }
+
+    // The size word covers the configuration fingerprint, compression marker, and checksum (if any) as well as the
+    // message itself.
+    bincode::serialize_into(&mut frame[..4], &(message_size + MESSAGE_OVERHEAD - 4))?;
+
+    #[cfg(feature = "fingerprint")]
+    {
+        frame[4] = fingerprint;
+    }
+
+    mark_compression(frame, UNCOMPRESSED);
+
+    #[cfg(feature = "checksum")]
+    {
+        let (header, body) = frame.split_at_mut(MESSAGE_OVERHEAD as usize);
+        let checksum = crc32fast::hash(&body[..message_size as usize]);
+        bincode::serialize_into(
+            &mut header[(4 + FINGERPRINT_SIZE + COMPRESSION_SIZE) as usize..],
+            &checksum,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Record how the message in `frame` (as written by `write_frame`) was compressed, if compression is enabled.
+///
+/// The marker isn't covered by the checksum, so this may be called after the frame has otherwise been completed.
+#[cfg(feature = "compression")]
+fn mark_compression(frame: &mut [u8], marker: u8) {
+    if frame.len() > 4 {
+        frame[(4 + FINGERPRINT_SIZE) as usize] = marker;
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+fn mark_compression(_frame: &mut [u8], _marker: u8) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Result};
+    use proptest::{
+        arbitrary::any, collection::vec, prop_assert, prop_assume, proptest, strategy::Strategy,
+    };
+    use std::{env, path::PathBuf, thread};
+
+    /// Returns the directory tests should create ring buffers in: `$IPMPSC_TEST_TMPDIR` if set, or the default
+    /// temporary directory otherwise.
+    ///
+    /// This allows e.g. CI machines with a small `/tmp` to run the tests (especially the property tests, which
+    /// create many ring buffers) somewhere roomier without changing `$TMPDIR` for everything else.
+    fn temp_dir() -> PathBuf {
+        env::var_os("IPMPSC_TEST_TMPDIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir)
+    }
+
+    fn create_temp(size_in_bytes: u32) -> super::Result<(String, SharedRingBuffer)> {
+        SharedRingBufferBuilder::new(size_in_bytes).create_temp_in(temp_dir())
+    }
+
+    #[derive(Debug)]
+    struct Case {
+        channel_size: u32,
+        data: Vec<Vec<u8>>,
+        sender_count: u32,
+    }
+
+    impl Case {
+        fn run(&self) -> Result<()> {
+            let (name, buffer) = create_temp(self.channel_size)?;
+            let rx = Receiver::new(buffer);
+
+            let receiver_thread = if self.sender_count == 1 {
+                // Only one sender means we can expect to receive in a predictable order:
+                let expected = self.data.clone();
+                thread::spawn(move || -> Result<()> {
+                    for item in &expected {
+                        let received = rx.recv::<Vec<u8>>()?;
+                        assert_eq!(item, &received);
+                    }
+
+                    Ok(())
+                })
+            } else {
+                // Multiple senders mean we'll receive in an unpredictable order, so just verify we receive the
+                // expected number of messages:
+                let expected = self.data.len() * self.sender_count as usize;
+                thread::spawn(move || -> Result<()> {
+                    for _ in 0..expected {
+                        rx.recv::<Vec<u8>>()?;
+                    }
+                    Ok(())
+                })
+            };
+
+            let data = Arc::new(self.data.clone());
+            let sender_threads = (0..self.sender_count)
+                .map(move |_| {
+                    os::test::fork({
+                        let name = name.clone();
+                        let data = data.clone();
+                        move || -> Result<()> {
+                            let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+                            for item in data.as_ref() {
+                                tx.send(item)?;
+                            }
+
+                            Ok(())
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            for thread in sender_threads {
+                thread.join().map_err(|e| anyhow!("{:?}", e))??;
+            }
+
+            receiver_thread.join().map_err(|e| anyhow!("{:?}", e))??;
+
+            Ok(())
+        }
+    }
+
+    fn arb_case() -> impl Strategy<Value = Case> {
+        ((32_u32..1024), (1_u32..5)).prop_flat_map(|(channel_size, sender_count)| {
+            // Each message is serialized with an eight-byte length prefix:
+            vec(vec(any::<u8>(), 0..=(channel_size as usize - 8)), 1..1024).prop_map(move |data| {
+                Case {
+                    channel_size,
+                    data,
+                    sender_count,
+                }
+            })
+        })
+    }
+
+    #[test]
+    fn simple_case() -> Result<()> {
+        Case {
+            channel_size: 1024,
+            data: (0..1024)
+                .map(|_| (0_u8..101).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            sender_count: 1,
+        }
+        .run()
+    }
+
+    #[test]
+    fn zero_copy() -> Result<()> {
+        #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+        struct Foo<'a> {
+            borrowed_str: &'a str,
+            #[serde(with = "serde_bytes")]
+            borrowed_bytes: &'a [u8],
+        }
+
+        let sent = Foo {
+            borrowed_str: "hi",
+            borrowed_bytes: &[0, 1, 2, 3],
+        };
+
+        let (name, buffer) = create_temp(256)?;
+        let mut rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        tx.send(&sent)?;
+        tx.send(&42_u32)?;
+
+        {
+            let mut rx = rx.zero_copy_context();
+            let received = rx.recv()?;
+
+            assert_eq!(sent, received);
+        }
+
+        assert_eq!(42_u32, rx.recv()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn zero_copy_commit_and_abort() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let mut rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        tx.send(&"hello")?;
+        tx.send(&"world")?;
+
+        {
+            let mut rx = rx.zero_copy_context();
+
+            assert_eq!("hello", rx.recv::<&str>()?);
+            assert!(matches!(rx.recv::<&str>(), Err(Error::AlreadyReceived)));
+            rx.abort();
+
+            assert_eq!("hello", rx.recv::<&str>()?);
+            rx.commit()?;
+
+            assert_eq!("world", rx.recv::<&str>()?);
+            rx.abort();
+        }
+
+        assert_eq!("world", rx.recv::<String>()?);
+        assert_eq!(None, rx.try_recv::<String>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn zero_copy_batch() -> Result<()> {
+        let (name, buffer) = create_temp(128)?;
+        let mut rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        // Move the read pointer partway through the ring buffer so that the batch below wraps around:
+        for _ in 0..2 {
+            tx.send(&"0123456789abcdef")?;
+            rx.recv::<String>()?;
+        }
+
+        let sent = ["first message...", "second message..", "third message..."];
+        for message in &sent {
+            tx.send(message)?;
+        }
+
+        {
+            let batch = rx.zero_copy_batch();
+            let received = (0..sent.len())
+                .map(|_| batch.recv::<&str>())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            assert_eq!(None, batch.try_recv::<&str>()?);
+            assert_eq!(3, batch.received());
+            assert_eq!(&sent[..], &received[..]);
+
+            batch.abort();
+        }
+
+        {
+            let batch = rx.zero_copy_batch();
+            let received = (0..sent.len())
+                .map(|_| batch.recv::<&str>())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            assert_eq!(&sent[..], &received[..]);
+        }
+
+        assert_eq!(None, rx.try_recv::<String>()?);
+
+        // Blocking waits for a message following those already in the batch:
+        tx.send(&"hello")?;
+
+        let sender = thread::spawn(move || -> Result<()> {
+            thread::sleep(Duration::from_millis(100));
+            Ok(tx.send(&"world")?)
+        });
+
+        {
+            let batch = rx.zero_copy_batch();
+
+            assert_eq!("hello", batch.recv::<&str>()?);
+            assert_eq!(None, batch.recv_timeout::<&str>(Duration::from_millis(0))?);
+            assert_eq!("world", batch.recv::<&str>()?);
+
+            batch.commit()?;
+        }
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        assert!(matches!(rx.try_recv::<String>(), Err(Error::NoSenders)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn slow_sender_with_recv_timeout() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let sender = os::test::fork(move || {
+            thread::sleep(Duration::from_secs(1));
+            tx.send(&42_u32).map_err(anyhow::Error::from)
+        })?;
+
+        loop {
+            if let Some(value) = rx.recv_timeout(Duration::from_millis(1))? {
+                assert_eq!(42_u32, value);
+                break;
+            }
+        }
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn slow_receiver_with_send_timeout() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let sender = os::test::fork(move || loop {
+            if tx.send_timeout(&42_u32, Duration::from_millis(1))? {
+                break Ok(());
+            }
+        })?;
+
+        thread::sleep(Duration::from_secs(1));
+        assert_eq!(42_u32, rx.recv()?);
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn sender_inherited_by_fork() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        // The parent's copy of `tx` keeps it counted while the child uses its own copy, which doesn't count:
+        let sender = os::test::fork(move || {
+            thread::sleep(Duration::from_millis(100));
+            for value in 0..3_u32 {
+                tx.send(&value)?;
+            }
+            Ok(())
+        })?;
+
+        for value in 0..3_u32 {
+            assert_eq!(value, rx.recv::<u32>()?);
+        }
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        assert!(matches!(rx.recv::<u32>(), Err(Error::NoSenders)));
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "futex", target_os = "linux"))]
+    #[test]
+    fn futex_wake_from_fork() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+
+        // Enough messages that the sender wraps around several times:
+        let count = 4 * rx.capacity() as u64 / u64::from(frame_size(8).unwrap());
+
+        let sender = os::test::fork(move || {
+            let tx = Sender::new(SharedRingBuffer::open(&name)?);
+            for value in 0..count {
+                // Give the receiver time to block on the futex before each message arrives:
+                thread::sleep(Duration::from_millis(10));
+                tx.send(&value)?;
+            }
+            Ok(())
+        })?;
+
+        for value in 0..count {
+            assert_eq!(value, rx.recv::<u64>()?);
+        }
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        assert!(matches!(rx.recv::<u64>(), Err(Error::NoSenders)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_message_size() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert_eq!(64, tx.max_message_size());
+
+        // A Vec<u8> is serialized with a u64 length prefix:
+        let largest = vec![0_u8; 56];
+        assert_eq!(64, tx.serialized_size(&largest)?);
+        assert_eq!(65, tx.serialized_size(&vec![0_u8; 57])?);
+
+        tx.send(&largest)?;
+        assert_eq!(largest, rx.recv::<Vec<u8>>()?);
+
+        assert!(matches!(
+            tx.send(&vec![0_u8; 57]),
+            Err(Error::MessageTooLarge)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn stats() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert_eq!(Stats::default(), rx.stats());
+
+        // The third message doesn't fit after the first two, so it wraps around:
+        let message = vec![0_u8; 16];
+        for _ in 0..3 {
+            tx.send(&message)?;
+            assert_eq!(message, rx.recv::<Vec<u8>>()?);
+        }
+
+        let frame_size = u64::from(tx.serialized_size(&message)? + MESSAGE_OVERHEAD);
+
+        assert_eq!(
+            Stats {
+                messages_sent: 3,
+                bytes_sent: 3 * frame_size,
+                wrap_count: 1,
+            },
+            rx.stats()
+        );
+        assert_eq!(rx.stats(), tx.stats());
+
+        Ok(())
+    }
+
+    #[test]
+    fn broadcast() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let first = BroadcastReceiver::new(buffer)?;
+        let second = BroadcastReceiver::new(SharedRingBuffer::open(&name)?)?;
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        // Space is only reclaimed once every receiver has received a message, so a slow receiver holds up senders:
+        let large = vec![0_u8; 40];
+        tx.send(&large)?;
+        assert_eq!(large, first.recv::<Vec<u8>>()?);
+        assert!(!tx.send_timeout(&large, Duration::from_millis(100))?);
+        assert_eq!(large, second.recv::<Vec<u8>>()?);
+
+        // Every receiver sees every message, even when senders must wait for space:
+        let sender = thread::spawn(move || -> Result<()> {
+            for message in 0..100_u32 {
+                tx.send(&message)?;
+            }
+            Ok(())
+        });
+
+        let receivers = vec![first, second]
+            .into_iter()
+            .map(|receiver| {
+                thread::spawn(move || -> Result<BroadcastReceiver> {
+                    for message in 0..100_u32 {
+                        assert_eq!(message, receiver.recv::<u32>()?);
+                    }
+                    Ok(receiver)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        let mut receivers = receivers
+            .into_iter()
+            .map(|receiver| receiver.join().map_err(|e| anyhow!("{:?}", e))?)
+            .collect::<Result<Vec<_>>>()?;
+
+        assert!(matches!(
+            receivers[0].try_recv::<u32>(),
+            Err(Error::NoSenders)
+        ));
+
+        // Each receiver occupies a slot in the header until it is dropped:
+        for _ in 2..MAX_BROADCAST_RECEIVERS {
+            receivers.push(BroadcastReceiver::new(SharedRingBuffer::open(&name)?)?);
+        }
+        assert!(matches!(
+            BroadcastReceiver::new(SharedRingBuffer::open(&name)?),
+            Err(Error::TooManyReceivers)
+        ));
+
+        receivers.pop();
+        BroadcastReceiver::new(SharedRingBuffer::open(&name)?)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn resize() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let mut rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        // Leave some messages queued on either side of a wrap marker, by first moving the read pointer to within
+        // two frames of the end:
+        let frame_size = (4 + MESSAGE_OVERHEAD) as usize;
+        for _ in 0..(rx.capacity() - 4) / frame_size - 1 {
+            tx.send(&0_u32)?;
+            rx.recv::<u32>()?;
+        }
+        for message in 1..4_u32 {
+            tx.send(&message)?;
+        }
+        assert_eq!(1, rx.stats().wrap_count);
+
+        assert!(matches!(rx.resize(32), Err(Error::BufferTooSmall)));
+
+        rx.resize(1024)?;
+
+        // Existing senders must reconnect:
+        assert!(matches!(tx.send(&0_u32), Err(Error::Resized)));
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+        assert_eq!(1024, tx.max_message_size());
+
+        let large = vec![42_u8; 512];
+        tx.send(&large)?;
+
+        for message in 1..4_u32 {
+            assert_eq!(message, rx.recv::<u32>()?);
+        }
+        assert_eq!(large, rx.recv::<Vec<u8>>()?);
+        assert_eq!(None, rx.try_recv::<Vec<u8>>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rpc() -> Result<()> {
+        let serve = |buffer| {
+            let mut server = Server::new(buffer);
+            let token = server.shutdown_token();
+            let thread =
+                thread::spawn(move || -> Result<()> { Ok(server.serve(|value: u32| value * 2)?) });
+            (token, thread)
+        };
+
+        let (name, buffer) = create_temp(256)?;
+        let (token, server) = serve(buffer);
+        let client = Arc::new(Client::new(&name, 256)?);
+
+        // Replies reach the right caller even when several threads share a client:
+        let callers = (0..4_u32)
+            .map(|caller| {
+                let client = client.clone();
+                thread::spawn(move || -> Result<()> {
+                    for value in (caller * 100)..(caller * 100 + 100) {
+                        assert_eq!(value * 2, client.call::<_, u32>(&value)?);
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for caller in callers {
+            caller.join().map_err(|e| anyhow!("{:?}", e))??;
+        }
+
+        assert_eq!(
+            Some(42),
+            client.call_timeout::<_, u32>(&21_u32, Duration::from_secs(10))?
+        );
+
+        token.shutdown()?;
+        server.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        // A call fails once the server goes away, but succeeds again once it restarts:
+        assert!(matches!(
+            client.call::<_, u32>(&21_u32),
+            Err(Error::NoReceiver)
+        ));
+
+        let (token, server) = serve(SharedRingBufferBuilder::new(256).create(&name)?);
+        assert_eq!(42, client.call::<_, u32>(&21_u32)?);
+
+        token.shutdown()?;
+        server.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn rpc_many_clients() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let mut server = Server::new(buffer);
+        let token = server.shutdown_token();
+        let thread = thread::spawn(move || -> Result<Server> {
+            server.serve(|value: u32| value * 2)?;
+            Ok(server)
+        });
+
+        // Each client goes away right after its only reply:
+        for value in 0..100_u32 {
+            assert_eq!(value * 2, Client::new(&name, 256)?.call::<_, u32>(&value)?);
+        }
+
+        token.shutdown()?;
+        let server = thread.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        // Only the most recent client is still remembered:
+        assert_eq!(1, server.clients.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn clear() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let large = vec![0_u8; 40];
+        tx.send(&large)?;
+        assert!(!tx.send_timeout(&large, Duration::from_millis(100))?);
+
+        // Clearing makes room for a waiting sender:
+        let sender = thread::spawn(move || -> Result<Sender> {
+            tx.send(&vec![1_u8; 40])?;
+            Ok(tx)
+        });
+
+        thread::sleep(Duration::from_millis(100));
+        rx.clear()?;
+
+        let tx = sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        assert_eq!(vec![1_u8; 40], rx.recv::<Vec<u8>>()?);
+
+        // The next message would need to wrap, but clearing moves both pointers back to the beginning:
+        rx.clear()?;
+        tx.send(&large)?;
+        rx.clear()?;
+        assert!(rx.is_empty());
+        assert_eq!(None, rx.try_recv::<Vec<u8>>()?);
+
+        tx.send(&42_u32)?;
+        assert_eq!(42, rx.recv::<u32>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn send_timeout() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert!(tx.send_timeout(&vec![0_u8; 40], Duration::from_millis(100))?);
+
+        let start = Instant::now();
+        assert!(!tx.send_timeout(&vec![1_u8; 40], Duration::from_millis(100))?);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // Only the first message was written:
+        assert_eq!(vec![0_u8; 40], rx.recv::<Vec<u8>>()?);
+        assert_eq!(None, rx.try_recv::<Vec<u8>>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn send_reporting() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert_eq!(SendOutcome::Immediate, tx.send_reporting(&vec![0_u8; 40])?);
+
+        let receiver = thread::spawn(move || -> Result<()> {
+            thread::sleep(Duration::from_millis(100));
+            assert_eq!(vec![0_u8; 40], rx.recv::<Vec<u8>>()?);
+            assert_eq!(vec![1_u8; 40], rx.recv::<Vec<u8>>()?);
+            Ok(())
+        });
+
+        match tx.send_reporting(&vec![1_u8; 40])? {
+            SendOutcome::Blocked { waited } => assert!(waited > Duration::from_millis(0)),
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+
+        receiver.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_stream() -> Result<()> {
+        use futures::{executor, StreamExt};
+
+        let (name, buffer) = create_temp(256)?;
+        let rx = AsyncReceiver::<u32>::new(Receiver::new(buffer));
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let sender = os::test::fork(move || {
+            for value in 0..100_u32 {
+                thread::sleep(Duration::from_millis(1));
+                tx.send(&value)?;
+            }
+            Ok(())
+        })?;
+
+        let received = executor::block_on(rx.take(100).collect::<Vec<_>>())
+            .into_iter()
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        assert_eq!((0..100).collect::<Vec<_>>(), received);
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "async", feature = "fork", unix))]
+    #[test]
+    fn async_fork() -> Result<()> {
+        use futures::executor;
+
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+
+        let child = fork_async(move || {
+            Sender::new(SharedRingBuffer::open(&name)?).send(&42_u32)?;
+            Ok(())
+        })?;
+
+        executor::block_on(child)?;
+        assert_eq!(Some(42_u32), rx.try_recv()?);
+
+        assert!(matches!(
+            executor::block_on(fork_async(|| Err(anyhow!("oops")))?),
+            Err(ChildError::Failed { message, .. }) if message == "oops"
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_recv() -> Result<()> {
+        use futures::executor;
+
+        let (name, buffer) = create_temp(256)?;
+        let rx = AsyncReceiver::<u32>::new(Receiver::new(buffer));
+
+        let sender = os::test::fork(move || {
+            let tx = Sender::new(SharedRingBuffer::open(&name)?);
+            for value in 0..10_u32 {
+                thread::sleep(Duration::from_millis(1));
+                tx.send(&value)?;
+            }
+            Ok(())
+        })?;
+
+        executor::block_on(async {
+            for expected in 0..10_u32 {
+                assert_eq!(expected, rx.recv().await?);
+            }
+
+            assert!(matches!(rx.recv().await, Err(Error::NoSenders)));
+
+            Ok::<_, Error>(())
+        })?;
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_poll_recv() -> Result<()> {
+        use futures::{executor, future};
+
+        let (name, buffer) = create_temp(256)?;
+        let rx = AsyncReceiver::<u32>::new(Receiver::new(buffer));
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let sender = thread::spawn(move || -> Result<()> {
+            thread::sleep(Duration::from_millis(100));
+            tx.send(&42_u32)?;
+            Ok(())
+        });
+
+        assert_eq!(
+            42,
+            executor::block_on(future::poll_fn(|cx| rx.poll_recv(cx)))?
+        );
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_sink() -> Result<()> {
+        use futures::{executor, stream, SinkExt};
+
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let mut tx = AsyncSender::<u32>::new(Sender::new(SharedRingBuffer::open(&name)?))?;
+
+        // The helper thread needs a clone of the sender, which would exceed this one's limit:
+        assert!(matches!(
+            AsyncSender::<u32>::new(Sender::with_max_clones(SharedRingBuffer::open(&name)?, 1)),
+            Err(Error::TooManySenders)
+        ));
+
+        let receiver = thread::spawn(move || -> Result<()> {
+            for expected in 0..1000_u32 {
+                assert_eq!(expected, rx.recv::<u32>()?);
+            }
+            Ok(())
+        });
+
+        executor::block_on(tx.send_all(&mut stream::iter((0..1000_u32).map(Ok))))?;
+
+        receiver.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn vectored() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let mut rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let parts: &[&[u8]] = &[b"info", &[1, 2, 3, 4], &[], &[5, 6]];
+
+        tx.send_vectored(parts)?;
+        tx.send_vectored(parts)?;
+
+        assert_eq!(parts, &rx.zero_copy_context().recv_vectored()?[..]);
+        assert_eq!(parts, &rx.recv::<Vec<Vec<u8>>>()?[..]);
+
+        assert!(matches!(
+            tx.send_vectored(&[&[0; 200], &[0; 200]]),
+            Err(Error::MessageTooLarge)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn all_or_none() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        // Per-message overhead means these fit individually but not all together:
+        assert!(matches!(
+            tx.send_all_or_none(&[[0_u8; 16]; 4]),
+            Err(Error::MessageTooLarge)
+        ));
+        assert_eq!(None, rx.try_recv::<[u8; 16]>()?);
+
+        let receiver = thread::spawn(move || -> Result<()> {
+            for round in 0..100_u32 {
+                for index in 0..3_u32 {
+                    assert_eq!((round, index), rx.recv()?);
+                }
+            }
+            Ok(())
+        });
+
+        for round in 0..100_u32 {
+            tx.send_all_or_none(&[(round, 0_u32), (round, 1), (round, 2)])?;
+        }
+
+        receiver.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn send_all() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert!(matches!(
+            tx.send_all(&[vec![1_u8; 8], vec![2_u8; 64]]),
+            Err(Error::MessageTooLarge)
+        ));
+        assert_eq!(None, rx.try_recv::<Vec<u8>>()?);
+
+        // Far more messages than fit in the ring buffer at once, so the sender must wait for the receiver between
+        // groups:
+        let values = (0..1000_u32).collect::<Vec<_>>();
+
+        let sender = thread::spawn({
+            let values = values.clone();
+            move || -> Result<()> {
+                tx.send_all(&values)?;
+                tx.send_all(&[u32::MAX])?;
+                Ok(())
+            }
+        });
+
+        for &value in &values {
+            assert_eq!(value, rx.recv::<u32>()?);
+        }
+        assert_eq!(u32::MAX, rx.recv::<u32>()?);
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn writer() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert!(matches!(
+            tx.send_writer(16, |writer| writer.write_all(&[0; 17])),
+            Err(Error::MessageTooLarge)
+        ));
+        assert!(matches!(
+            tx.send_writer(16, |_| Ok(())),
+            Err(Error::ZeroSizedMessage)
+        ));
+        assert_eq!(None, rx.try_recv::<u8>()?);
+
+        let receiver = thread::spawn(move || -> Result<()> {
+            for round in 0..100_u32 {
+                assert_eq!((round, format!("round {}", round)), rx.recv()?);
+            }
+            Ok(())
+        });
+
+        for round in 0..100_u32 {
+            tx.send_writer(32, |writer| {
+                bincode::serialize_into(writer, &(round, format!("round {}", round)))
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            })?;
+        }
+
+        receiver.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn spsc() -> Result<()> {
+        let (name, buffer) = SharedRingBufferBuilder::new(64)
+            .single_producer(true)
+            .create_temp_in(temp_dir())?;
+        let rx = Receiver::new(buffer);
+
+        let tx = SpscSender::new(SharedRingBuffer::open(&name)?)?;
+        assert!(matches!(
+            SpscSender::new(SharedRingBuffer::open(&name)?),
+            Err(Error::TooManySenders)
+        ));
+
+        assert!(matches!(
+            SpscSender::new(create_temp(64)?.1),
+            Err(Error::WrongSenderKind)
+        ));
+
+        let sender = thread::spawn(move || -> Result<()> {
+            // Small enough to wrap often and block on a full ring buffer:
+            for value in 0..10_000_u32 {
+                tx.send(&value)?;
+            }
+            Ok(())
+        });
+
+        for value in 0..10_000_u32 {
+            assert_eq!(value, rx.recv::<u32>()?);
+        }
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        assert!(matches!(rx.recv::<u32>(), Err(Error::NoSenders)));
+
+        // Once the first sender has disconnected, another may take its place, but not an ordinary `Sender`:
+        let tx = SpscSender::new(SharedRingBuffer::open(&name)?)?;
+        assert!(tx.try_send(&42_u32)?);
+        assert_eq!(42_u32, rx.recv()?);
+
+        assert!(matches!(
+            Sender::new(SharedRingBuffer::open(&name)?).send(&42_u32),
+            Err(Error::WrongSenderKind)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn wake_one() -> Result<()> {
+        let (name, buffer) = SharedRingBufferBuilder::new(32)
+            .wake_one(true)
+            .create_temp_in(temp_dir())?;
+        let rx = Receiver::new(buffer);
+
+        // Several senders contending for a small ring buffer, with messages of varying size:
+        let senders = (0..4_u32)
+            .map(|index| {
+                let buffer = SharedRingBuffer::open(&name)?;
+                Ok(thread::spawn(move || -> Result<()> {
+                    let tx = Sender::new(buffer);
+                    for value in 0..500_u32 {
+                        tx.send(&vec![index; (value % 5) as usize])?;
+                    }
+                    Ok(())
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut counts = [0; 4];
+        for _ in 0..2000 {
+            if let Some(&index) = rx.recv::<Vec<u32>>()?.first() {
+                counts[index as usize] += 1;
+            }
+        }
+
+        for sender in senders {
+            sender.join().map_err(|e| anyhow!("{:?}", e))??;
+        }
+
+        // Every sender ran to completion (a fifth of each sender's messages are empty vectors):
+        assert_eq!([400; 4], counts);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bounded() -> Result<()> {
+        let dir = tempfile::tempdir_in(temp_dir())?;
+        let path = dir.path().join("queue");
+        let name = path.to_str().ok_or_else(|| anyhow!("non-UTF-8 path"))?;
+
+        assert!(matches!(
+            SharedRingBuffer::create_bounded(name, 0, 4),
+            Err(Error::BufferTooSmall)
+        ));
+
+        let buffer = SharedRingBuffer::create_bounded(name, 3, 4)?;
+        let rx = Receiver::new(buffer.clone());
+        let tx = Sender::new(buffer);
+
+        assert!(matches!(
+            tx.send_all_or_none(&[1_u32, 2, 3, 4]),
+            Err(Error::MessageTooLarge)
+        ));
+
+        // The limit is reached before space runs out, wherever the pointers happen to be.
+        for round in 0..10_u32 {
+            for value in 0..3 {
+                assert!(tx.try_send(&(round * 3 + value))?);
+            }
+            assert!(!tx.try_send(&0_u32)?);
+
+            for value in 0..3 {
+                assert_eq!(round * 3 + value, rx.recv::<u32>()?);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn available_messages() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert_eq!(0, rx.available_messages()?);
+
+        // Cycle through the ring buffer a few times so the queued messages eventually straddle a wrap marker.
+        for round in 0..10_u32 {
+            for value in 0..3 {
+                tx.send(&(round * 3 + value))?;
+            }
+            assert_eq!(3, rx.available_messages()?);
+
+            for value in 0..3 {
+                assert_eq!(round * 3 + value, rx.recv::<u32>()?);
+                assert_eq!(2 - value as usize, rx.available_messages()?);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn len() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let frame = (4 + MESSAGE_OVERHEAD) as usize;
+
+        assert!(rx.is_empty());
+        assert_eq!(0, rx.len());
+        assert_eq!(64 + frame, rx.capacity());
+
+        // Advance the pointers so the next batch wraps around:
+        for _ in 0..8 {
+            tx.send(&0_u32)?;
+            assert_eq!(0, rx.recv::<u32>()?);
+        }
+
+        let mut sent = 0;
+        while tx.try_send(&1_u32)? {
+            sent += 1;
+            assert!(!rx.is_empty());
+            assert!(rx.len() >= sent * frame);
+            assert!(rx.len() <= rx.capacity());
+        }
+
+        for _ in 0..sent {
+            assert_eq!(1, rx.recv::<u32>()?);
+        }
+
+        // This skips any wrap marker left by the last `try_send`:
+        assert_eq!(None, rx.try_recv::<u32>()?);
+
+        assert!(rx.is_empty());
+        assert_eq!(0, rx.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn shutdown() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+        let _other = tx.try_clone()?;
+        let token = tx.shutdown_token();
+
+        // Fill the ring buffer so the next send blocks.
+        while tx.try_send(&42_u32)? {}
+
+        let sender = thread::spawn(move || tx.send(&42_u32));
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(!token.is_shutdown());
+        token.shutdown()?;
+        assert!(token.is_shutdown());
+
+        assert!(matches!(
+            sender.join().map_err(|e| anyhow!("{:?}", e))?,
+            Err(Error::Interrupted)
+        ));
+
+        // The receiver gets everything sent before the shutdown, and then the terminal error, repeatedly:
+        for value in rx.drain::<u32>() {
+            assert_eq!(42, value?);
+        }
+        assert!(matches!(rx.recv::<u32>(), Err(Error::Interrupted)));
+        assert!(matches!(rx.try_recv::<u32>(), Err(Error::Interrupted)));
+
+        // A blocked receiver is woken:
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let receiver = thread::spawn(move || rx.byte_iter().count());
+
+        thread::sleep(Duration::from_millis(100));
+        tx.shutdown()?;
+
+        assert_eq!(0, receiver.join().map_err(|e| anyhow!("{:?}", e))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn tagged() -> Result<()> {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Foo<'a>(&'a str);
+
+        impl Tagged for Foo<'_> {
+            const TAG: u16 = 1;
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Bar(u32);
+
+        impl Tagged for Bar {
+            const TAG: u16 = 2;
+        }
+
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert!(rx.try_recv_tagged()?.is_none());
+
+        tx.send_tagged(&Bar(42))?;
+        tx.send_tagged(&Foo("hello"))?;
+
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            let message = rx.recv_tagged()?;
+            match message.tag() {
+                Foo::TAG => {
+                    assert!(matches!(
+                        message.deserialize::<Bar>(),
+                        Err(Error::TagMismatch {
+                            found: 1,
+                            expected: 2
+                        })
+                    ));
+                    received.push(message.deserialize::<Foo>()?.0.to_owned());
+                }
+                Bar::TAG => received.push(message.deserialize::<Bar>()?.0.to_string()),
+                tag => panic!("unexpected tag {}", tag),
+            }
+        }
+
+        assert_eq!(vec!["42", "hello"], received);
+
+        Ok(())
+    }
+
+    #[test]
+    fn schema() -> Result<()> {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct V1 {
+            x: u32,
+        }
+
+        impl Schema for V1 {
+            const SCHEMA_HASH: u64 = schema_hash("V1 { x: u32 }");
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct V2 {
+            x: u32,
+            y: u32,
+        }
+
+        impl Schema for V2 {
+            const SCHEMA_HASH: u64 = schema_hash("V2 { x: u32, y: u32 }");
+        }
+
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert_eq!(None, rx.try_recv_checked::<V1>()?);
+
+        tx.send_checked(&V1 { x: 42 })?;
+
+        match rx.recv_checked::<V2>() {
+            Err(Error::SchemaMismatch { found, expected }) => {
+                assert_eq!(V1::SCHEMA_HASH, found);
+                assert_eq!(V2::SCHEMA_HASH, expected);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // The mismatched message was left in place:
+        assert_eq!(V1 { x: 42 }, rx.recv_checked::<V1>()?);
+        assert_eq!(None, rx.try_recv_checked::<V1>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn recv_filtered() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        for value in 0..10_u32 {
+            tx.send(&value)?;
+        }
+
+        assert_eq!(5, rx.recv_filtered(|&value: &u32| value == 5)?);
+        assert_eq!(6, rx.recv::<u32>()?);
+        assert_eq!(8, rx.recv_filtered(|&value: &u32| value % 4 == 0)?);
+        assert_eq!(9, rx.recv::<u32>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_send() -> Result<()> {
+        let (name, buffer) = create_temp(32)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert!(matches!(tx.try_send(&()), Err(Error::ZeroSizedMessage)));
+        assert!(matches!(
+            tx.try_send(&vec![0_u8; 25]),
+            Err(Error::MessageTooLarge)
+        ));
+
+        // Fill the ring buffer repeatedly, starting at a different position each round so that messages must
+        // sometimes wrap around:
+        for round in 0..10_u32 {
+            let mut sent = 0;
+            while tx.try_send(&[round; 3])? {
+                sent += 1;
+            }
+            assert!(sent > 0);
+
+            for _ in 0..sent {
+                assert_eq!(Some([round; 3]), rx.try_recv()?);
+            }
+            assert_eq!(None, rx.try_recv::<[u32; 3]>()?);
+
+            tx.send(&(round as u8))?;
+            assert_eq!(Some(round as u8), rx.try_recv()?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn peek() -> Result<()> {
+        let (name, buffer) = create_temp(32)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert_eq!(None, rx.peek::<u32>()?);
+
+        // Send messages of varying size so that some of them follow a wrap marker:
+        let sender = thread::spawn(move || -> Result<()> {
+            for value in 0..100_u32 {
+                tx.send(&vec![value; (value % 5) as usize])?;
+            }
+            Ok(())
+        });
+
+        for value in 0..100_u32 {
+            let expected = vec![value; (value % 5) as usize];
+
+            let peeked = loop {
+                if let Some(peeked) = rx.peek::<Vec<u32>>()? {
+                    break peeked;
+                }
+                thread::yield_now();
+            };
+
+            assert_eq!(expected, peeked);
+            assert_eq!(Some(expected.clone()), rx.peek()?);
+            assert_eq!(expected, rx.recv::<Vec<u32>>()?);
+        }
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        assert!(matches!(rx.peek::<Vec<u32>>(), Err(Error::NoSenders)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn drain() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert_eq!(0, rx.drain::<u32>().count());
+
+        // Fill the ring buffer repeatedly, starting at a different position each round so that some rounds wrap
+        // around partway through:
+        for round in 0..10_u32 {
+            let mut sent = Vec::new();
+            while tx.try_send(&(round * 100 + sent.len() as u32))? {
+                sent.push(round * 100 + sent.len() as u32);
+            }
+
+            assert_eq!(sent, rx.drain::<u32>().collect::<Result<Vec<_>, _>>()?);
+            assert_eq!(None, rx.try_recv::<u32>()?);
+
+            tx.send(&(round as u8))?;
+            assert_eq!(Some(round as u8), rx.try_recv()?);
+        }
+
+        // The iterator stops after the first error, leaving the offending message in place:
+        tx.send(&1_u8)?;
+        let mut drain = rx.drain::<u32>();
+        assert!(matches!(drain.next(), Some(Err(Error::Bincode(_)))));
+        assert!(drain.next().is_none());
+        assert_eq!(Some(1_u8), rx.try_recv()?);
+
+        drop(tx);
+        assert_eq!(0, rx.drain::<u32>().count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn zero_timeout() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let start = Instant::now();
+        for _ in 0..1000 {
+            assert_eq!(None, rx.recv_timeout::<u32>(Duration::from_secs(0))?);
+        }
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        tx.send(&42_u32)?;
+        assert_eq!(Some(42), rx.recv_timeout::<u32>(Duration::from_secs(0))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn recv_deadline() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let mut rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        // Repeated calls share the deadline rather than each waiting afresh:
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(100);
+        for _ in 0..10 {
+            assert_eq!(None, rx.recv_deadline::<u32>(deadline)?);
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(100));
+        assert!(elapsed < Duration::from_millis(500));
+
+        // A deadline in the past still receives an available message:
+        tx.send(&42_u32)?;
+        assert_eq!(Some(42), rx.recv_deadline::<u32>(start)?);
+
+        tx.send(&"hello")?;
+        {
+            let mut context = rx.zero_copy_context();
+            assert_eq!(Some("hello"), context.recv_deadline::<&str>(deadline)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn clone_count() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+
+        let tx = Sender::with_max_clones(buffer, 3);
+        assert_eq!(1, tx.clone_count());
+
+        let tx2 = tx.clone();
+        let tx3 = tx2.try_clone()?;
+        assert_eq!(3, tx.clone_count());
+        assert!(matches!(tx.try_clone(), Err(Error::TooManySenders)));
+
+        // `clone` doesn't enforce the limit, but still counts toward it:
+        let tx4 = tx.clone();
+        assert_eq!(4, tx.clone_count());
+        assert!(matches!(tx3.try_clone(), Err(Error::TooManySenders)));
+
+        drop(tx2);
+        drop(tx4);
+        assert_eq!(2, tx3.clone_count());
+        let _tx5 = tx3.try_clone()?;
+
+        // Separately constructed senders have their own count:
+        let other = Sender::new(SharedRingBuffer::open(&name)?);
+        assert_eq!(1, other.clone_count());
+        assert_eq!(3, tx.clone_count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sync_receiver() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = SyncReceiver::new(Receiver::new(buffer));
+
+        let sender = thread::spawn(move || -> Result<()> {
+            let tx = Sender::new(SharedRingBuffer::open(&name)?);
+            for value in 0..1000_u32 {
+                tx.send(&value)?;
+            }
+            Ok(())
+        });
+
+        let workers = (0..4)
+            .map(|_| {
+                let rx = rx.clone();
+                thread::spawn(move || -> Result<Vec<u32>> {
+                    let mut received = Vec::new();
+                    loop {
+                        match rx.recv() {
+                            Ok(value) => received.push(value),
+                            Err(Error::NoSenders) => break Ok(received),
+                            Err(e) => break Err(e.into()),
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        let mut received = Vec::new();
+        for worker in workers {
+            received.extend(worker.join().map_err(|e| anyhow!("{:?}", e))??);
+        }
+        received.sort_unstable();
+
+        // Every message was received exactly once:
+        assert_eq!((0..1000).collect::<Vec<_>>(), received);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bytes() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let (relay_name, relay_buffer) = create_temp(256)?;
+        let relay_rx = Receiver::new(relay_buffer);
+
+        tx.send(&"hello")?;
+        tx.send(&42_u32)?;
+        drop(tx);
+
+        // Relay messages without knowing their types:
+        {
+            let relay_tx = Sender::new(SharedRingBuffer::open(&relay_name)?);
+            for bytes in rx.byte_iter() {
+                relay_tx.send_bytes(&bytes?)?;
+            }
+        }
+
+        assert_eq!("hello", relay_rx.recv::<String>()?);
+        assert_eq!(42_u32, relay_rx.recv()?);
+        assert!(relay_rx.byte_iter().next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn lock_timeout() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer.clone());
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        // Simulate a stuck sender by holding the lock in another thread:
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let (unlock_tx, unlock_rx) = std::sync::mpsc::channel::<()>();
+        let holder = thread::spawn(move || -> Result<()> {
+            let _lock = buffer.0.buffer().lock()?;
+            locked_tx.send(())?;
+            let _ = unlock_rx.recv();
+            Ok(())
+        });
+
+        locked_rx.recv()?;
+
+        let start = Instant::now();
+        assert!(matches!(
+            tx.send_timeout(&1_u32, Duration::from_millis(50)),
+            Err(Error::LockTimeout)
+        ));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(matches!(tx.try_send(&1_u32), Err(Error::LockTimeout)));
+
+        drop(unlock_tx);
+        holder.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        assert!(tx.try_send(&2_u32)?);
+        assert_eq!(2_u32, rx.recv()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn zero_sized() -> Result<()> {
+        let (name, buffer) = create_temp(64)?;
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+        assert!(matches!(tx.send(&()), Err(Error::ZeroSizedMessage)));
+        drop((tx, buffer));
+
+        let (name, buffer) = SharedRingBufferBuilder::new(64)
+            .allow_zero_sized(true)
+            .create_temp_in(temp_dir())?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        tx.send(&())?;
+        tx.send_all_or_none(&[(), ()])?;
+        tx.send_writer(16, |_| Ok(()))?;
+        for _ in 0..4 {
+            rx.recv::<()>()?;
+        }
+        assert_eq!(None, rx.try_recv::<()>()?);
+
+        let receiver = thread::spawn(move || -> Result<()> {
+            for value in 0..100_u32 {
+                rx.recv::<()>()?;
+                assert_eq!(value, rx.recv()?);
+            }
+            Ok(())
+        });
+
+        for value in 0..100_u32 {
+            tx.send(&())?;
+            tx.send(&value)?;
+        }
+
+        receiver.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn run() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let sender = thread::spawn(move || -> Result<()> {
+            for value in 0..100_u32 {
+                tx.send(&value)?;
+            }
+            Ok(())
+        });
+
+        // Runs until the sender disconnects:
+        let mut received = Vec::new();
+        Receiver::new(buffer.clone()).run(|value: u32| {
+            received.push(value);
+            ControlFlow::Continue(())
+        })?;
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        assert_eq!((0..100).collect::<Vec<_>>(), received);
+
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+        tx.send(&"hello")?;
+        tx.send(&"world")?;
+
+        // Runs until the handler says to stop, leaving the remaining message in place:
+        let mut received = Vec::new();
+        Receiver::new(buffer.clone()).run_zero_copy(|context| {
+            received.push(context.recv::<&str>()?.to_owned());
+            Ok(ControlFlow::Break(()))
+        })?;
+
+        assert_eq!(vec!["hello".to_owned()], received);
+        assert_eq!(Some("world".to_owned()), Receiver::new(buffer).try_recv()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn debug() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+        let _clone = tx.clone();
+
+        tx.send(&42_u32)?;
+
+        let rx_debug = format!("{:?}", rx);
+        assert!(rx_debug.contains("size_in_bytes: 256"), "{}", rx_debug);
+        assert!(
+            rx_debug.contains(&format!("occupied: {}", 4 + MESSAGE_OVERHEAD)),
+            "{}",
+            rx_debug
+        );
+
+        let tx_debug = format!("{:?}", tx);
+        assert!(tx_debug.contains("clone_count: 2"), "{}", tx_debug);
+
+        assert_eq!(42, rx.recv::<u32>()?);
+        assert!(format!("{:?}", rx).contains("occupied: 0"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn buffer_id() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let (_, other) = create_temp(256)?;
+
+        assert_eq!(
+            buffer.buffer_id(),
+            SharedRingBuffer::open(&name)?.buffer_id()
+        );
+        assert_ne!(buffer.buffer_id(), other.buffer_id());
+
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+        assert_eq!(rx.buffer_id, tx.buffer_id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn temp_named() -> Result<()> {
+        let prefix = format!("ipmpsc-test-{}-", std::process::id());
+        let (name, buffer) = SharedRingBuffer::create_temp_named(&prefix, 256)?;
+
+        assert!(Path::new(&name)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name.starts_with(&prefix))
+            .unwrap_or(false));
+
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        tx.send(&42_u32)?;
+        assert_eq!(42, rx.recv::<u32>()?);
+
+        drop(tx);
+        drop(rx);
+
+        assert!(!Path::new(&name).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn heartbeat() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        // No receiver has recorded a heartbeat yet:
+        assert!(tx.peer_stale(Duration::from_secs(60)));
+
+        let rx = Receiver::new(buffer);
+
+        assert!(!tx.peer_stale(Duration::from_secs(60)));
+        assert!(!rx.peer_stale(Duration::from_secs(60)));
+
+        thread::sleep(Duration::from_millis(50));
+
+        assert!(tx.peer_stale(Duration::from_millis(10)));
+        assert!(rx.peer_stale(Duration::from_millis(10)));
+
+        rx.heartbeat();
+        tx.heartbeat();
+
+        assert!(!tx.peer_stale(Duration::from_secs(10)));
+        assert!(!rx.peer_stale(Duration::from_secs(10)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn drain_after_senders_disconnect() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+
+        // No sender has connected yet, so we should wait rather than report disconnection:
+        assert_eq!(None, rx.recv_timeout::<u32>(Duration::from_millis(10))?);
+
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+        let tx2 = tx.clone();
+
+        tx.send(&1_u32)?;
+        tx2.send(&2_u32)?;
+
+        drop(tx);
+
+        assert_eq!(1_u32, rx.recv()?);
+
+        drop(tx2);
+
+        assert_eq!(2_u32, rx.recv()?);
+        assert!(matches!(rx.recv::<u32>(), Err(Error::NoSenders)));
+        assert!(matches!(rx.try_recv::<u32>(), Err(Error::NoSenders)));
+
+        let sender = thread::spawn(move || -> Result<()> {
+            let tx = Sender::new(SharedRingBuffer::open(&name)?);
+            thread::sleep(Duration::from_millis(100));
+            tx.send(&3_u32)?;
+            Ok(())
+        });
+
+        // A blocked receiver should be woken when the last sender disconnects:
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(3_u32, rx.recv()?);
+        assert!(matches!(rx.recv::<u32>(), Err(Error::NoSenders)));
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn raw() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let mut rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        tx.send(&(1_u8, "hello"))?;
+        tx.send(&(2_u8, 42_u32))?;
+
+        for _ in 0..2 {
+            let raw = rx
+                .try_recv_raw()?
+                .ok_or_else(|| anyhow!("expected message"))?;
+            match raw[0] {
+                1 => assert_eq!((1, "hello"), bincode::deserialize::<(u8, &str)>(&raw)?),
+                2 => assert_eq!((2, 42), bincode::deserialize::<(u8, u32)>(&raw)?),
+                tag => return Err(anyhow!("unexpected tag: {}", tag)),
+            }
+        }
+
+        assert!(rx.try_recv_raw()?.is_none());
+
+        tx.send(&"borrowed")?;
+        assert_eq!("borrowed", *rx.recv_ref::<&str>()?);
+        assert!(rx.try_recv_raw()?.is_none());
+
+        tx.send(&3_u32)?;
+
+        // An aborted message is received again:
+        rx.try_recv_raw()?
+            .ok_or_else(|| anyhow!("expected message"))?
+            .abort();
+
+        let raw = rx
+            .try_recv_raw()?
+            .ok_or_else(|| anyhow!("expected message"))?;
+        assert_eq!(3_u32, bincode::deserialize(&raw)?);
+        raw.commit()?;
+
+        assert!(rx.try_recv_raw()?.is_none());
+
+        // Bytes from some other codec are passed through untouched:
+        tx.send_bytes(b"\x08\x96\x01")?;
+        tx.send_bytes(b"\x12\x07testing")?;
+
+        let mut buffer = vec![0xFF; 64];
+        rx.recv_into(&mut buffer)?;
+        assert_eq!(b"\x08\x96\x01", &buffer[..]);
+        assert_eq!(b"\x12\x07testing", &*rx.recv_bytes_ref()?);
+        assert!(rx.try_recv_raw()?.is_none());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn temp_in_with_mode() -> Result<()> {
+        let dir = tempfile::tempdir_in(temp_dir())?;
+        let (name, buffer) = SharedRingBufferBuilder::new(256)
+            .mode(0o660)
+            .create_temp_in(dir.path())?;
+
+        assert!(Path::new(&name).starts_with(dir.path()));
+        assert_eq!(0o660, fs::metadata(&name)?.permissions().mode() & 0o777);
+
+        buffer.set_mode(0o640)?;
+
+        assert_eq!(0o640, fs::metadata(&name)?.permissions().mode() & 0o777);
+
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        tx.send(&42_u32)?;
+        assert_eq!(42_u32, rx.recv()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn flush_to_disk() -> Result<()> {
+        let dir = tempfile::tempdir_in(temp_dir())?;
+        let path = dir.path().join("queue");
+        let name = path.to_str().ok_or_else(|| anyhow!("non-UTF-8 path"))?;
+
+        {
+            let buffer = SharedRingBuffer::create(name, 256)?;
+            Sender::new(buffer.clone()).send(&42_u32)?;
+            buffer.flush_to_disk()?;
+        }
+
+        // The unreceived message is still there after reopening the file:
+        let rx = Receiver::new(SharedRingBuffer::open(name)?);
+        assert_eq!(Some(42_u32), rx.try_recv()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_reporting() -> Result<()> {
+        let dir = tempfile::tempdir_in(temp_dir())?;
+        let path = dir.path().join("queue");
+        let name = path.to_str().ok_or_else(|| anyhow!("non-UTF-8 path"))?;
+
+        let (buffer, outcome) = SharedRingBuffer::create_reporting(name, 256)?;
+        assert_eq!(CreateOutcome::Created, outcome);
+
+        Sender::new(buffer).send(&42_u32)?;
+
+        let (buffer, outcome) = SharedRingBuffer::create_reporting(name, 256)?;
+        assert_eq!(CreateOutcome::Truncated, outcome);
+        assert_eq!(None, Receiver::new(buffer).try_recv::<u32>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_or_resume() -> Result<()> {
+        let dir = tempfile::tempdir_in(temp_dir())?;
+        let path = dir.path().join("queue");
+        let name = path.to_str().ok_or_else(|| anyhow!("non-UTF-8 path"))?;
+
+        {
+            let buffer = SharedRingBuffer::create_or_resume(name, 256)?;
+            let rx = Receiver::new(buffer.clone());
+            let tx = Sender::new(buffer);
+            tx.send(&1_u32)?;
+            tx.send(&2_u32)?;
+            assert_eq!(1_u32, rx.recv()?);
+        }
+
+        // The unreceived message is preserved:
+        let buffer = SharedRingBuffer::create_or_resume(name, 256)?;
+        assert_eq!(Some(2_u32), Receiver::new(buffer).try_recv()?);
+
+        // A ring buffer of a different size is replaced:
+        let buffer = SharedRingBuffer::create_or_resume(name, 128)?;
+        assert_eq!(128, buffer.size_in_bytes());
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(name)?);
+        assert_eq!(None, rx.try_recv::<u32>()?);
+        tx.send(&3_u32)?;
+        assert_eq!(3_u32, rx.recv()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn iter() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+
+        let sender = thread::spawn(move || -> Result<()> {
+            let tx = Sender::new(SharedRingBuffer::open(&name)?);
+            for value in 0..10_u32 {
+                tx.send(&value)?;
+            }
+            Ok(())
+        });
+
+        assert_eq!(
+            vec![0, 2, 4],
+            rx.iter::<u32>()
+                .take(5)
+                .filter(|value| matches!(value, Ok(value) if value % 2 == 0))
+                .collect::<super::Result<Vec<_>>>()?
+        );
+
+        // The iterator ends once the sender disconnects:
+        assert_eq!(
+            (5..10).collect::<Vec<_>>(),
+            rx.iter::<u32>().collect::<super::Result<Vec<_>>>()?
+        );
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn select() -> Result<()> {
+        let buffers = (0..3)
+            .map(|_| create_temp(256))
+            .collect::<super::Result<Vec<_>>>()?;
+        let receivers = buffers
+            .iter()
+            .map(|(_, buffer)| Receiver::new(buffer.clone()))
+            .collect::<Vec<_>>();
+        let senders = buffers
+            .iter()
+            .map(|(name, _)| Ok(Sender::new(SharedRingBuffer::open(name)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let selector = Selector::new(&receivers.iter().collect::<Vec<_>>());
+
+        assert_eq!(None, selector.try_select()?);
+        assert_eq!(None, selector.select_timeout(Duration::from_millis(10))?);
+
+        let sender = thread::spawn({
+            let name = buffers[2].0.clone();
+            move || -> Result<()> {
+                thread::sleep(Duration::from_millis(50));
+                Sender::new(SharedRingBuffer::open(&name)?).send(&2_u32)?;
+                Ok(())
+            }
+        });
+
+        assert_eq!(2, selector.select()?);
+        assert_eq!(Some(2_u32), receivers[2].try_recv()?);
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        // When several receivers are ready, each is selected in turn:
+        for (index, tx) in senders.iter().enumerate() {
+            tx.send(&(index as u32))?;
+        }
+
+        let mut selected = Vec::new();
+        for _ in 0..6 {
+            selected.push(selector.select()?);
+        }
+        assert_eq!(vec![0, 1, 2, 0, 1, 2], selected);
+
+        for (index, rx) in receivers.iter().enumerate() {
+            assert_eq!(Some(index as u32), rx.try_recv()?);
+        }
+
+        assert_eq!(None, selector.try_select()?);
+
+        // A receiver whose senders have all disconnected is ready, since receiving won't block:
+        drop(senders);
+        assert!(selector.try_select()?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn attach() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let tx = Sender::new(buffer);
+
+        {
+            let rx = Receiver::attach(&name)?;
+            tx.send(&1_u32)?;
+            assert_eq!(1_u32, rx.recv()?);
+        }
+
+        // Messages sent while no receiver is attached are received by the next one:
+        tx.send(&2_u32)?;
+        tx.send(&3_u32)?;
+        let rx = ReceiverBuilder::new().spin_count(10).attach(&name)?;
+        assert_eq!(2_u32, rx.recv()?);
+        assert_eq!(3_u32, rx.recv()?);
+
+        // Uninitialized files are rejected:
+        let file = tempfile::NamedTempFile::new_in(temp_dir())?;
+        let path = file
+            .path()
+            .to_str()
+            .ok_or_else(|| anyhow!("non-UTF-8 path"))?;
+        assert!(matches!(Receiver::attach(path), Err(Error::Uninitialized)));
+
+        file.as_file().set_len(1024)?;
+        assert!(matches!(Receiver::attach(path), Err(Error::Uninitialized)));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fork_outcomes() -> Result<()> {
+        fn outcome(fun: impl FnOnce() -> Result<()> + Send + 'static) -> Result<ChildError> {
+            os::test::fork(fun)?
+                .join()
+                .map_err(|e| anyhow!("{:?}", e))?
+                .err()
+                .ok_or_else(|| anyhow!("expected child to fail"))
+        }
+
+        os::test::fork(|| Ok(()))?
+            .join()
+            .map_err(|e| anyhow!("{:?}", e))??;
+
+        assert!(matches!(
+            outcome(|| Err(anyhow!("oops")))?,
+            ChildError::Failed { message, .. } if message == "oops"
+        ));
+        assert!(matches!(
+            outcome(|| panic!("boom"))?,
+            ChildError::Panicked { message, .. } if message == "boom"
+        ));
+        assert!(matches!(
+            outcome(|| std::process::exit(3))?,
+            ChildError::Exited { status: 3, .. }
+        ));
+        assert!(matches!(
+            outcome(|| std::process::abort())?,
+            ChildError::Signaled {
+                signal: libc::SIGABRT,
+                ..
+            }
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn valid_deadlines() {
+        for &clock in &[libc::CLOCK_REALTIME, libc::CLOCK_MONOTONIC] {
+            for timeout in [
+                Duration::from_secs(0),
+                Duration::from_nanos(999_999_999),
+                Duration::from_secs(u64::MAX),
+                Duration::new(u64::MAX, 999_999_999),
+            ]
+            .iter()
+            {
+                let deadline = os::deadline(clock, *timeout);
+                assert!(deadline.tv_sec >= 0);
+                assert!((0..1_000_000_000).contains(&deadline.tv_nsec));
+            }
+        }
+    }
+
+    #[test]
+    fn spinning_receiver() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = ReceiverBuilder::new().spin_count(1000).build(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        let sender = thread::spawn(move || -> Result<()> {
+            for value in 0..1000_u32 {
+                tx.send(&value)?;
+            }
+            Ok(())
+        });
+
+        for expected in 0..1000_u32 {
+            assert_eq!(expected, rx.recv()?);
+        }
+
+        assert!(matches!(rx.recv::<u32>(), Err(Error::NoSenders)));
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        Ok(())
+    }
+
+    #[test]
+    fn receiver_alive() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert!(!tx.is_receiver_alive());
+
+        let rx = Receiver::new(buffer);
+
+        assert!(tx.is_receiver_alive());
+
+        drop(rx);
+
+        assert!(!tx.is_receiver_alive());
+
+        Ok(())
+    }
+
+    #[test]
+    fn mutex_kinds() -> Result<()> {
+        let mut kinds = vec![MutexKind::Default, MutexKind::Normal];
+
+        if cfg!(all(target_os = "linux", target_env = "gnu")) {
+            kinds.push(MutexKind::Adaptive);
+        }
+
+        if cfg!(target_os = "linux") {
+            kinds.push(MutexKind::Robust);
+        }
+
+        for kind in kinds {
+            let (name, buffer) = SharedRingBufferBuilder::new(256)
+                .mutex_kind(kind)
+                .create_temp_in(temp_dir())?;
+            let rx = Receiver::new(buffer);
+            let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+            tx.send(&42_u32)?;
+            assert_eq!(42_u32, rx.recv()?);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn robust_mutex_survives_owner_death() -> Result<()> {
+        let (name, buffer) = SharedRingBufferBuilder::new(256)
+            .mutex_kind(MutexKind::Robust)
+            .create_temp_in(temp_dir())?;
+        let rx = Receiver::new(buffer);
+
+        os::test::fork({
+            let name = name.clone();
+            move || {
+                // Exit while holding the lock, keeping the mapping alive so the OS can find the mutex:
+                let buffer = SharedRingBuffer::open(&name)?;
+                mem::forget(buffer.0.buffer().lock()?);
+                mem::forget(buffer);
+                Ok(())
+            }
+        })?
+        .join()
+        .map_err(|e| anyhow!("{:?}", e))??;
+
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        tx.send(&42_u32)?;
+        assert_eq!(42_u32, rx.recv()?);
+
+        // A sender killed while writing a message leaves nothing behind for the receiver, since the write pointer
+        // is only advanced once the message is complete:
+        let outcome = os::test::fork({
+            let name = name.clone();
+            move || {
+                let tx = Sender::new(SharedRingBuffer::open(&name)?);
+                tx.send_writer(16, |writer| {
+                    writer.write_all(&[0xFF; 8])?;
+                    std::process::abort()
+                })?;
+                Ok(())
+            }
+        })?
+        .join()
+        .map_err(|e| anyhow!("{:?}", e))?;
+
+        assert!(matches!(outcome, Err(ChildError::Signaled { .. })));
+        assert_eq!(None, rx.try_recv::<u32>()?);
+
+        tx.send(&43_u32)?;
+        assert_eq!(43_u32, rx.recv()?);
+
+        Ok(())
     }
 
-    fn send_timeout_0(
-        &self,
-        value: &impl Serialize,
-        wait_until_empty: bool,
-        timeout: Option<Duration>,
-    ) -> Result<bool> {
-        let buffer = self.0 .0.buffer();
-        let map = self.0 .0.map_mut();
+    #[test]
+    fn old_layout_version() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
 
-        let size = bincode::serialized_size(value)? as u32;
+        // Simulate a ring buffer created by a version of ipmpsc which predates layout versioning:
+        buffer.0.buffer().header().flags.store(FLAG_64_BIT, Relaxed);
 
-        if size == 0 {
-            return Err(Error::ZeroSizedMessage);
-        }
+        assert!(matches!(
+            SharedRingBuffer::open(&name),
+            Err(Error::VersionTooOld {
+                found: 0,
+                min: LAYOUT_VERSION
+            })
+        ));
 
-        let map_len = map.len();
+        buffer.0.buffer().header().flags.store(flags(), Relaxed);
 
-        if (BEGINNING + size + 8) as usize > map_len {
-            return Err(Error::MessageTooLarge);
-        }
+        SharedRingBuffer::open(&name)?;
 
-        let mut lock = buffer.lock()?;
-        let mut deadline = None;
-        let mut write;
-        loop {
-            write = buffer.header().write.load(Relaxed);
-            let read = buffer.header().read.load(Relaxed);
+        // A file which isn't a ring buffer is rejected even if its first word happens to match the flags:
+        let header = buffer.0.buffer().header();
+        header.canary.store(0, Relaxed);
 
-            if write == read || (write > read && !wait_until_empty) {
-                if (write + size + 8) as usize <= map_len {
-                    break;
-                } else if read != BEGINNING {
-                    assert!(write > BEGINNING);
+        assert!(matches!(
+            SharedRingBuffer::open(&name),
+            Err(Error::IncompatibleRingBuffer)
+        ));
 
-                    bincode::serialize_into(
-                        &mut map[write as usize..(write + 4) as usize],
-                        &0_u32,
-                    )?;
-                    write = BEGINNING;
-                    buffer.header().write.store(write, Release);
-                    lock.notify_all()?;
-                    continue;
-                }
-            } else if write + size + 8 <= read && !wait_until_empty {
-                break;
-            }
+        header.canary.store(HEADER_CANARY, Relaxed);
 
-            let now = Instant::now();
-            deadline = deadline.or_else(|| timeout.map(|timeout| now + timeout));
+        SharedRingBuffer::open(&name)?;
 
-            if deadline.map(|deadline| deadline > now).unwrap_or(true) {
-                lock.timed_wait(&self.0 .0, deadline.map(|deadline| deadline - now))?;
-            } else {
-                return Ok(false);
-            }
+        Ok(())
+    }
+
+    #[test]
+    fn migrate() -> Result<()> {
+        let (old_name, old) = create_temp(64)?;
+        let mut rx = Receiver::new(old.clone());
+        let old_tx = Sender::new(SharedRingBuffer::open(&old_name)?);
+
+        for value in 0..3_u32 {
+            old_tx.send(&value)?;
         }
 
-        let start = write + 4;
-        bincode::serialize_into(&mut map[write as usize..start as usize], &size)?;
+        // Migrating to a ring buffer which could never hold the messages fails:
+        assert!(matches!(
+            rx.migrate_to(create_temp(8)?.1),
+            Err(Error::MessageTooLarge)
+        ));
 
-        let end = start + size;
-        bincode::serialize_into(&mut map[start as usize..end as usize], value)?;
+        // Migrating to one which is currently too full does nothing:
+        let (full_name, full) = create_temp(64)?;
+        let full_tx = Sender::new(SharedRingBuffer::open(&full_name)?);
+        while full_tx.try_send(&42_u32)? {}
+        assert!(!rx.migrate_to(full)?);
+        assert_eq!(3, rx.available_messages()?);
 
-        buffer.header().write.store(end, Release);
+        let (new_name, new) = create_temp(256)?;
+        let new_tx = Sender::new(SharedRingBuffer::open(&new_name)?);
+        new_tx.send(&100_u32)?;
 
-        lock.notify_all()?;
+        assert!(rx.migrate_to(new)?);
 
-        Ok(true)
-    }
-}
+        // The messages were consumed from the old ring buffer:
+        let header = old.0.buffer().header();
+        assert_eq!(header.write.load(Relaxed), header.read.load(Relaxed));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use anyhow::{anyhow, Result};
-    use proptest::{arbitrary::any, collection::vec, prop_assume, proptest, strategy::Strategy};
-    use std::thread;
+        new_tx.send(&101_u32)?;
 
-    #[derive(Debug)]
-    struct Case {
-        channel_size: u32,
-        data: Vec<Vec<u8>>,
-        sender_count: u32,
+        for value in [100_u32, 0, 1, 2, 101].iter() {
+            assert_eq!(*value, rx.recv::<u32>()?);
+        }
+
+        Ok(())
     }
 
-    impl Case {
-        fn run(&self) -> Result<()> {
-            let (name, buffer) = SharedRingBuffer::create_temp(self.channel_size)?;
-            let rx = Receiver::new(buffer);
+    #[test]
+    fn corrupt_header() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer.clone());
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
 
-            let receiver_thread = if self.sender_count == 1 {
-                // Only one sender means we can expect to receive in a predictable order:
-                let expected = self.data.clone();
-                thread::spawn(move || -> Result<()> {
-                    for item in &expected {
-                        let received = rx.recv::<Vec<u8>>()?;
-                        assert_eq!(item, &received);
-                    }
+        let header = buffer.0.buffer().header();
+        header.canary.store(0, Relaxed);
 
-                    Ok(())
-                })
-            } else {
-                // Multiple senders mean we'll receive in an unpredictable order, so just verify we receive the
-                // expected number of messages:
-                let expected = self.data.len() * self.sender_count as usize;
-                thread::spawn(move || -> Result<()> {
-                    for _ in 0..expected {
-                        rx.recv::<Vec<u8>>()?;
-                    }
-                    Ok(())
-                })
-            };
+        assert!(matches!(tx.send(&42_u32), Err(Error::CorruptHeader)));
+        assert!(matches!(rx.try_recv::<u32>(), Err(Error::CorruptHeader)));
 
-            let data = Arc::new(self.data.clone());
-            let sender_threads = (0..self.sender_count)
-                .map(move |_| {
-                    os::test::fork({
-                        let name = name.clone();
-                        let data = data.clone();
-                        move || -> Result<()> {
-                            let tx = Sender::new(SharedRingBuffer::open(&name)?);
+        header.canary.store(HEADER_CANARY, Relaxed);
+        header.flags.store(0, Relaxed);
 
-                            for item in data.as_ref() {
-                                tx.send(item)?;
-                            }
+        assert!(matches!(tx.send(&42_u32), Err(Error::CorruptHeader)));
 
-                            Ok(())
-                        }
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?;
+        header.flags.store(flags(), Relaxed);
 
-            for thread in sender_threads {
-                thread.join().map_err(|e| anyhow!("{:?}", e))??;
+        tx.send(&42_u32)?;
+        assert_eq!(42_u32, rx.recv()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn crate_version_mismatch() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+
+        // Simulate a ring buffer created by a hypothetical ipmpsc 1.x:
+        let header = buffer.0.buffer().header();
+        header.crate_version.store(1 << 16, Relaxed);
+
+        match SharedRingBuffer::open(&name) {
+            Err(Error::VersionMismatch { found, expected }) => {
+                assert_eq!("1", found);
+                assert_eq!(format_crate_version(crate_version()), expected);
             }
+            result => panic!("unexpected result: {:?}", result.map(drop)),
+        }
 
-            receiver_thread.join().map_err(|e| anyhow!("{:?}", e))??;
+        header.crate_version.store(crate_version(), Relaxed);
+
+        SharedRingBuffer::open(&name)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn tiny_buffers() -> Result<()> {
+        assert!(matches!(create_temp(0), Err(Error::BufferTooSmall)));
+
+        let (name, buffer) = create_temp(1)?;
+        assert_eq!(1, buffer.size_in_bytes());
+        assert_eq!(name, buffer.name());
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
 
+        let receiver = thread::spawn(move || -> Result<()> {
+            for expected in 0..100_u8 {
+                assert_eq!(expected, rx.recv::<u8>()?);
+            }
             Ok(())
+        });
+
+        for value in 0..100_u8 {
+            tx.send(&value)?;
         }
-    }
 
-    fn arb_case() -> impl Strategy<Value = Case> {
-        ((32_u32..1024), (1_u32..5)).prop_flat_map(|(channel_size, sender_count)| {
-            vec(vec(any::<u8>(), 0..(channel_size as usize - 24)), 1..1024).prop_map(move |data| {
-                Case {
-                    channel_size,
-                    data,
-                    sender_count,
-                }
-            })
-        })
+        receiver.join().map_err(|e| anyhow!("{:?}", e))??;
+
+        assert!(matches!(tx.send(&1_u16), Err(Error::MessageTooLarge)));
+
+        Ok(())
     }
 
     #[test]
-    fn simple_case() -> Result<()> {
-        Case {
-            channel_size: 1024,
-            data: (0..1024)
-                .map(|_| (0_u8..101).collect::<Vec<_>>())
-                .collect::<Vec<_>>(),
-            sender_count: 1,
+    fn capacity() -> Result<()> {
+        let (name, buffer) = create_temp(100)?;
+        assert_eq!(
+            HEADER_SIZE as usize + 100 + MESSAGE_OVERHEAD as usize + 4,
+            buffer.0.buffer().map().len()
+        );
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        // This many messages fit in a newly created ring buffer:
+        let n = 16;
+        let fit = 1 + (100 - n) / (n + MESSAGE_OVERHEAD);
+        assert!(matches!(
+            tx.send_all_or_none(&vec![[0_u8; 16]; fit as usize + 1]),
+            Err(Error::MessageTooLarge)
+        ));
+        tx.send_all_or_none(&vec![[0_u8; 16]; fit as usize])?;
+        for _ in 0..fit {
+            assert_eq!(Some([0_u8; 16]), rx.try_recv()?);
         }
-        .run()
+
+        // The largest single message is exactly the capacity (a `Vec<u8>` has an eight-byte length prefix):
+        let (name, buffer) = create_temp(100)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        tx.send(&vec![7_u8; 92])?;
+        assert_eq!(Some(vec![7_u8; 92]), rx.try_recv()?);
+        assert!(matches!(
+            tx.send(&vec![7_u8; 93]),
+            Err(Error::MessageTooLarge)
+        ));
+
+        Ok(())
     }
 
     #[test]
-    fn zero_copy() -> Result<()> {
-        #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
-        struct Foo<'a> {
-            borrowed_str: &'a str,
-            #[serde(with = "serde_bytes")]
-            borrowed_bytes: &'a [u8],
-        }
+    fn message_framing() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
+        let rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
 
-        let sent = Foo {
-            borrowed_str: "hi",
-            borrowed_bytes: &[0, 1, 2, 3],
+        tx.send(&(0x0102_0304_u32, "hi"))?;
+
+        // Integers are little-endian and fixed-width, with a u64 string length:
+        let message = [4, 3, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0, b'h', b'i'];
+        let map = tx.buffer.0.buffer().map();
+        let frame = &map[BEGINNING as usize..][..(MESSAGE_OVERHEAD as usize + message.len())];
+
+        assert_eq!(
+            (message.len() as u32 + MESSAGE_OVERHEAD - 4).to_le_bytes(),
+            frame[..4]
+        );
+        assert_eq!(message, frame[MESSAGE_OVERHEAD as usize..]);
+
+        assert_eq!((0x0102_0304_u32, "hi".to_owned()), rx.recv()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn config() -> Result<()> {
+        let config = Config {
+            endian: Endian::Big,
+            int_encoding: IntEncoding::Varint,
         };
+        let (name, buffer) = create_temp(1024)?;
+        let mut rx = Receiver::new(buffer).with_config(config);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?).with_config(config);
 
-        let (name, buffer) = SharedRingBuffer::create_temp(256)?;
-        let mut rx = Receiver::new(buffer);
-        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+        tx.send(&(0x0102_0304_u32, "hi"))?;
 
-        tx.send(&sent)?;
-        tx.send(&42_u32)?;
+        // A varint-encoded u32 this large is a marker byte followed by the value itself, here big-endian:
+        let message = [252, 1, 2, 3, 4, 2, b'h', b'i'];
+        let map = tx.buffer.0.buffer().map();
+        let frame = &map[BEGINNING as usize..][..(MESSAGE_OVERHEAD as usize + message.len())];
+        assert_eq!(message, frame[MESSAGE_OVERHEAD as usize..]);
 
-        {
-            let mut rx = rx.zero_copy_context();
-            let received = rx.recv()?;
+        assert_eq!((0x0102_0304_u32, "hi".to_owned()), rx.recv()?);
 
-            assert_eq!(sent, received);
-        }
+        tx.try_clone()?.send_all_or_none(&[1_u64, 1 << 40])?;
+        assert_eq!(1_u64, rx.recv()?);
+        assert_eq!(1_u64 << 40, rx.recv()?);
 
-        assert_eq!(42_u32, rx.recv()?);
+        let parts: &[&[u8]] = &[b"foo", b"ba"];
+        tx.send_vectored(parts)?;
+        assert_eq!(parts, &rx.zero_copy_context().recv_vectored()?[..]);
 
         Ok(())
     }
 
+    #[cfg(feature = "fingerprint")]
     #[test]
-    fn slow_sender_with_recv_timeout() -> Result<()> {
-        let (name, buffer) = SharedRingBuffer::create_temp(256)?;
+    fn config_mismatch() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
         let rx = Receiver::new(buffer);
         let tx = Sender::new(SharedRingBuffer::open(&name)?);
 
-        let sender = os::test::fork(move || {
-            thread::sleep(Duration::from_secs(1));
-            tx.send(&42_u32).map_err(anyhow::Error::from)
-        })?;
+        tx.with_config(Config {
+            endian: Endian::Big,
+            ..Config::default()
+        })
+        .send(&42_u32)?;
 
-        loop {
-            if let Some(value) = rx.recv_timeout(Duration::from_millis(1))? {
-                assert_eq!(42_u32, value);
-                break;
+        assert!(matches!(
+            rx.try_recv::<u32>(),
+            Err(Error::ConfigMismatch {
+                found: 0b1010_0001,
+                expected: CONFIG_FINGERPRINT
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "pollable", target_os = "linux"))]
+    #[test]
+    fn pollable() -> Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        fn readable(fd: &impl AsRawFd) -> Result<bool> {
+            let mut pollfd = libc::pollfd {
+                fd: fd.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+
+            match unsafe { libc::poll(&mut pollfd, 1, 1000) } {
+                -1 => Err(io::Error::last_os_error().into()),
+                0 => Ok(false),
+                _ => Ok(pollfd.revents & libc::POLLIN != 0),
             }
         }
 
-        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+        let (name, buffer) = create_temp(256)?;
+        let rx = PollableReceiver::new(Receiver::new(buffer))?;
+        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+
+        assert_eq!(None, rx.try_recv::<u32>()?);
+
+        tx.send(&1_u32)?;
+        tx.send(&2_u32)?;
+
+        assert!(readable(&rx)?);
+        assert_eq!(Some(1_u32), rx.try_recv()?);
+        assert_eq!(Some(2_u32), rx.try_recv()?);
+        assert_eq!(None, rx.try_recv::<u32>()?);
+
+        // The descriptor is re-armed, and becomes readable again when a sender disconnects:
+        let sender = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            drop(tx);
+        });
+
+        assert!(readable(&rx)?);
+        assert!(matches!(rx.try_recv::<u32>(), Err(Error::NoSenders)));
+
+        sender.join().map_err(|e| anyhow!("{:?}", e))?;
 
         Ok(())
     }
 
+    #[cfg(feature = "checksum")]
     #[test]
-    fn slow_receiver_with_send_timeout() -> Result<()> {
-        let (name, buffer) = SharedRingBuffer::create_temp(256)?;
+    fn checksum_mismatch() -> Result<()> {
+        let (name, buffer) = create_temp(256)?;
         let rx = Receiver::new(buffer);
         let tx = Sender::new(SharedRingBuffer::open(&name)?);
 
-        let sender = os::test::fork(move || loop {
-            if tx.send_timeout(&42_u32, Duration::from_millis(1))? {
-                break Ok(());
-            }
-        })?;
+        tx.send(&42_u32)?;
+        tx.send(&43_u32)?;
 
-        thread::sleep(Duration::from_secs(1));
-        assert_eq!(42_u32, rx.recv()?);
+        // Simulate a buggy process scribbling over the first message:
+        tx.buffer.0.map_mut()[(BEGINNING + MESSAGE_OVERHEAD) as usize] ^= 0xFF;
 
-        sender.join().map_err(|e| anyhow!("{:?}", e))??;
+        assert!(matches!(
+            rx.try_recv::<u32>(),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+
+        // The corrupt message is discarded, so later ones may still be received:
+        assert_eq!(Some(43_u32), rx.try_recv()?);
+        assert_eq!(None, rx.try_recv::<u32>()?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compression() -> Result<()> {
+        let (name, buffer) = create_temp(4096)?;
+        let mut rx = Receiver::new(buffer);
+        let tx = Sender::new(SharedRingBuffer::open(&name)?).with_compression(Compression::Lz4);
+
+        // A repetitive message is stored compressed, taking much less space than its serialized form:
+        let repetitive = vec![7_u8; 1024];
+        tx.send(&repetitive)?;
+        assert!(rx.len() < 512);
+        assert_eq!(repetitive, rx.recv::<Vec<u8>>()?);
+
+        // A message which wouldn't get smaller is stored as serialized:
+        tx.send(&42_u32)?;
+        assert_eq!(42_u32, rx.recv::<u32>()?);
+
+        // Compressed messages can't be borrowed directly from the ring buffer:
+        tx.send(&repetitive)?;
+        assert!(matches!(rx.recv_ref::<Vec<u8>>(), Err(Error::Compressed)));
+
+        // Receivers handle messages from senders with and without compression alike:
+        let plain = tx.try_clone()?.with_compression(Compression::None);
+        plain.send(&repetitive)?;
+        tx.send(&repetitive)?;
+        assert_eq!(repetitive, rx.recv::<Vec<u8>>()?);
+        assert_eq!(repetitive, rx.recv::<Vec<u8>>()?);
 
         Ok(())
     }
 
     proptest! {
+        #[test]
+        fn zero_copy_borrow_survives_wrap(
+            channel_size in 64_u32..512,
+            lengths in vec(1_usize..48, 1..32)
+        ) {
+            let result = (|| -> Result<()> {
+                let (name, buffer) = create_temp(channel_size)?;
+                let mut rx = Receiver::new(buffer);
+
+                // Message `i` consists of `lengths[i]` copies of the byte `i`:
+                let sender = thread::spawn({
+                    let lengths = lengths.clone();
+                    move || -> Result<()> {
+                        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+                        for (index, &length) in lengths.iter().enumerate() {
+                            tx.send(&vec![index as u8; length])?;
+                        }
+                        Ok(())
+                    }
+                });
+
+                for (index, &length) in lengths.iter().enumerate() {
+                    let mut context = rx.zero_copy_context();
+                    let bytes = context.recv::<&[u8]>()?;
+
+                    // Give the sender plenty of opportunity to fill (and wrap around) the rest of the ring buffer
+                    // while we hold the borrow:
+                    thread::sleep(Duration::from_millis(1));
+
+                    assert_eq!(&vec![index as u8; length][..], bytes);
+                }
+
+                sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+                Ok(())
+            })();
+
+            prop_assert!(result.is_ok(), "error: {:?}", result.unwrap_err());
+        }
+
+        #[test]
+        fn maximum_size_messages_always_fit(
+            channel_size in 9_u32..256,
+            padding in vec(0_usize..16, 1..32)
+        ) {
+            let result = (|| -> Result<()> {
+                let (name, buffer) = create_temp(channel_size)?;
+                let rx = Receiver::new(buffer);
+
+                // A `Vec<u8>` has an eight-byte length prefix, so this is the largest message the ring buffer can
+                // hold.  Each one is preceded by a small message (if any padding) so that the read and write
+                // pointers are left at arbitrary positions when it is sent.
+                let max = channel_size as usize - 8;
+
+                let sender = thread::spawn({
+                    let padding = padding.clone();
+                    move || -> Result<()> {
+                        let tx = Sender::new(SharedRingBuffer::open(&name)?);
+                        for (index, &padding) in padding.iter().enumerate() {
+                            if padding != 0 {
+                                tx.send(&vec![0_u8; padding.min(max)])?;
+                            }
+                            tx.send(&vec![index as u8; max])?;
+                        }
+                        Ok(())
+                    }
+                });
+
+                for (index, &padding) in padding.iter().enumerate() {
+                    if padding != 0 {
+                        assert_eq!(vec![0_u8; padding.min(max)], rx.recv::<Vec<u8>>()?);
+                    }
+                    assert_eq!(vec![index as u8; max], rx.recv::<Vec<u8>>()?);
+                }
+
+                sender.join().map_err(|e| anyhow!("{:?}", e))??;
+
+                Ok(())
+            })();
+
+            prop_assert!(result.is_ok(), "error: {:?}", result.unwrap_err());
+        }
+
         #[test]
         fn arbitrary_case(case in arb_case()) {
             let result = thread::spawn(move || {
@@ -0,0 +1,130 @@
+use crate::{os::Header, Receiver, Result};
+use serde::Deserialize;
+use std::{
+    io,
+    os::{
+        linux::net::SocketAddrExt,
+        unix::{
+            io::{AsRawFd, RawFd},
+            net::{SocketAddr, UnixDatagram},
+        },
+    },
+    sync::atomic::{self, Ordering::SeqCst},
+};
+
+/// Returns the address of the socket a [`PollableReceiver`](struct.PollableReceiver.html) listens on for the ring
+/// buffer with the specified ID.
+///
+/// This lives in the abstract namespace, so there's no file to clean up, and the ID is random, so it won't collide
+/// with that of any other ring buffer.
+fn address(buffer_id: u64) -> io::Result<SocketAddr> {
+    SocketAddr::from_abstract_name(format!("ipmpsc-{:016x}", buffer_id))
+}
+
+/// Wake the [`PollableReceiver`](struct.PollableReceiver.html) for this ring buffer, if it has been armed since
+/// it was last woken.
+///
+/// The write pointer (or sender count) must already have been updated, so that either the receiver sees the
+/// update after arming or we see it armed.
+pub fn notify(header: &Header) {
+    atomic::fence(SeqCst);
+
+    if header.notify_armed.swap(0, SeqCst) != 0 {
+        // This is best effort: if the receiver has gone away, there's nothing to wake.
+        if let (Ok(socket), Ok(address)) = (
+            UnixDatagram::unbound(),
+            address(header.buffer_id.load(SeqCst)),
+        ) {
+            let _ = socket.set_nonblocking(true);
+            let _ = socket.send_to_addr(&[0], &address);
+        }
+    }
+}
+
+/// Wrapper for a [`Receiver`](struct.Receiver.html) which exposes a file descriptor that becomes readable when a
+/// message may be available, so that it can be registered with `epoll`, `poll`, etc. rather than dedicating a
+/// thread to blocking on the ring buffer.  Only available on Linux, with the `pollable` feature enabled.
+///
+/// The descriptor is the receiving end of a Unix datagram socket to which senders write after publishing a message
+/// (or disconnecting), since the ring buffer's condition variable can't be waited on alongside other descriptors.
+/// Wakeups are edge-triggered in effect: once the descriptor becomes readable, call
+/// [`PollableReceiver::try_recv`](struct.PollableReceiver.html#method.try_recv) until it returns `Ok(None)` (or
+/// an error) before waiting on the descriptor again.  Each call drains the socket and re-arms it, so no explicit
+/// draining is needed.
+///
+/// Only one [`PollableReceiver`](struct.PollableReceiver.html) may exist per ring buffer at a time.
+pub struct PollableReceiver {
+    receiver: Receiver,
+    socket: UnixDatagram,
+}
+
+impl PollableReceiver {
+    /// Constructs a [`PollableReceiver`](struct.PollableReceiver.html) which wraps the specified
+    /// [`Receiver`](struct.Receiver.html).
+    ///
+    /// The descriptor starts out armed, so it will become readable once a message is sent, but any messages sent
+    /// before this was constructed should be received using
+    /// [`PollableReceiver::try_recv`](struct.PollableReceiver.html#method.try_recv) first.
+    pub fn new(receiver: Receiver) -> Result<Self> {
+        let socket = UnixDatagram::bind_addr(&address(receiver.buffer.buffer_id())?)?;
+        socket.set_nonblocking(true)?;
+
+        let pollable = Self { receiver, socket };
+        pollable.arm()?;
+
+        Ok(pollable)
+    }
+
+    /// Attempt to read a message without blocking, as with
+    /// [`Receiver::try_recv`](struct.Receiver.html#method.try_recv), after draining and re-arming the descriptor.
+    pub fn try_recv<T>(&self) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.arm()?;
+        self.receiver.try_recv()
+    }
+
+    /// Returns the wrapped [`Receiver`](struct.Receiver.html), e.g. to use its blocking methods.
+    pub fn receiver(&self) -> &Receiver {
+        &self.receiver
+    }
+
+    /// Consumes this wrapper, returning the [`Receiver`](struct.Receiver.html) it wraps.
+    ///
+    /// If the descriptor is still armed, the next sender to publish a message will attempt to wake it anyway, which
+    /// is harmless since nothing is listening any more.
+    pub fn into_inner(self) -> Receiver {
+        self.receiver
+    }
+
+    /// Drain any pending wakeups from the socket and ask senders for another.
+    fn arm(&self) -> Result<()> {
+        let mut datagram = [0; 1];
+        loop {
+            match self.socket.recv(&mut datagram) {
+                Ok(_) => (),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // Pairs with the fence in `notify` (see there):
+        self.receiver
+            .buffer
+            .0
+            .buffer()
+            .header()
+            .notify_armed
+            .store(1, SeqCst);
+        atomic::fence(SeqCst);
+
+        Ok(())
+    }
+}
+
+impl AsRawFd for PollableReceiver {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
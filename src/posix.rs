@@ -1,17 +1,20 @@
-use crate::{Error, Result};
+use crate::{Error, MutexKind, Result};
 use memmap2::MmapMut;
 use std::{
     cell::UnsafeCell,
-    mem::MaybeUninit,
+    mem::{self, MaybeUninit},
     os::raw::c_long,
     sync::{
-        atomic::{AtomicU32, Ordering::Relaxed},
+        atomic::{AtomicU32, AtomicU64, Ordering::Relaxed},
         Arc,
     },
-    time::{Duration, SystemTime},
+    time::Duration,
 };
 use tempfile::NamedTempFile;
 
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+use std::{thread, time::Instant};
+
 // libc::PTHREAD_PROCESS_SHARED doesn't exist for Android for some
 // reason, so we need to declare it ourselves:
 #[cfg(target_os = "android")]
@@ -38,10 +41,80 @@ pub struct Header {
     condition: UnsafeCell<libc::pthread_cond_t>,
     pub read: AtomicU32,
     pub write: AtomicU32,
+    pub receiver_alive: AtomicU32,
+    pub sender_count: AtomicU32,
+    pub sender_connected: AtomicU32,
+    pub notify_seq: AtomicU32,
+    pub waiting_senders: AtomicU32,
+    pub waiting_receivers: AtomicU32,
+    pub receiver_spinning: AtomicU32,
+    pub notify_armed: AtomicU32,
+    pub buffer_id: AtomicU64,
+    pub receiver_heartbeat: AtomicU64,
+    pub sender_heartbeat: AtomicU64,
+    pub messages_sent: AtomicU64,
+    pub bytes_sent: AtomicU64,
+    pub wrap_count: AtomicU64,
+    pub cursors: [AtomicU32; crate::MAX_BROADCAST_RECEIVERS],
+    pub generation: AtomicU32,
+    pub options: AtomicU32,
+    pub shutdown: AtomicU32,
+    pub max_messages: AtomicU32,
+    pub crate_version: AtomicU32,
+    pub canary: AtomicU64,
+}
+
+/// Configure `attr` according to `kind`.
+unsafe fn set_mutex_kind(attr: *mut libc::pthread_mutexattr_t, kind: MutexKind) -> Result<()> {
+    match kind {
+        MutexKind::Default => Ok(()),
+
+        MutexKind::Normal => nonzero!(libc::pthread_mutexattr_settype(
+            attr,
+            libc::PTHREAD_MUTEX_NORMAL
+        )),
+
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        MutexKind::Adaptive => nonzero!(libc::pthread_mutexattr_settype(
+            attr,
+            libc::PTHREAD_MUTEX_ADAPTIVE_NP
+        )),
+
+        #[cfg(target_os = "linux")]
+        MutexKind::Robust => nonzero!(libc::pthread_mutexattr_setrobust(
+            attr,
+            libc::PTHREAD_MUTEX_ROBUST
+        )),
+
+        #[allow(unreachable_patterns)]
+        kind => Err(Error::Runtime(format!(
+            "{:?} mutexes are not supported on this platform",
+            kind
+        ))),
+    }
+}
+
+/// Treat `EOWNERDEAD` (i.e. the previous owner of a robust mutex died while holding it) as success, marking the
+/// mutex consistent so it remains usable.
+///
+/// This is safe for the ring buffer since the read and write pointers are only ever updated atomically, so a
+/// process which dies while holding the lock cannot leave them in an inconsistent state.
+#[cfg(target_os = "linux")]
+unsafe fn owner_dead_ok(mutex: *mut libc::pthread_mutex_t, result: i32) -> i32 {
+    if result == libc::EOWNERDEAD {
+        libc::pthread_mutex_consistent(mutex)
+    } else {
+        result
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+unsafe fn owner_dead_ok(_mutex: *mut libc::pthread_mutex_t, result: i32) -> i32 {
+    result
 }
 
 impl Header {
-    pub fn init(&self) -> Result<()> {
+    pub fn init(&self, mutex_kind: MutexKind, options: u32, max_messages: u32) -> Result<()> {
         self.flags.store(crate::flags(), Relaxed);
 
         unsafe {
@@ -51,6 +124,7 @@ impl Header {
                 attr.as_mut_ptr(),
                 PTHREAD_PROCESS_SHARED
             ))?;
+            set_mutex_kind(attr.as_mut_ptr(), mutex_kind)?;
             nonzero!(libc::pthread_mutex_init(self.mutex.get(), attr.as_ptr()))?;
             nonzero!(libc::pthread_mutexattr_destroy(attr.as_mut_ptr()))?;
 
@@ -60,12 +134,40 @@ impl Header {
                 attr.as_mut_ptr(),
                 PTHREAD_PROCESS_SHARED
             ))?;
+            #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+            nonzero!(libc::pthread_condattr_setclock(
+                attr.as_mut_ptr(),
+                CONDITION_CLOCK
+            ))?;
             nonzero!(libc::pthread_cond_init(self.condition.get(), attr.as_ptr()))?;
             nonzero!(libc::pthread_condattr_destroy(attr.as_mut_ptr()))?;
         }
 
         self.read.store(crate::BEGINNING, Relaxed);
         self.write.store(crate::BEGINNING, Relaxed);
+        self.receiver_alive.store(0, Relaxed);
+        self.sender_count.store(0, Relaxed);
+        self.sender_connected.store(0, Relaxed);
+        self.notify_seq.store(0, Relaxed);
+        self.waiting_senders.store(0, Relaxed);
+        self.waiting_receivers.store(0, Relaxed);
+        self.receiver_spinning.store(0, Relaxed);
+        self.notify_armed.store(0, Relaxed);
+        self.buffer_id.store(crate::random_id(), Relaxed);
+        self.receiver_heartbeat.store(0, Relaxed);
+        self.sender_heartbeat.store(0, Relaxed);
+        self.messages_sent.store(0, Relaxed);
+        self.bytes_sent.store(0, Relaxed);
+        self.wrap_count.store(0, Relaxed);
+        for cursor in &self.cursors {
+            cursor.store(crate::broadcast::FREE, Relaxed);
+        }
+        self.generation.store(0, Relaxed);
+        self.options.store(options, Relaxed);
+        self.shutdown.store(0, Relaxed);
+        self.max_messages.store(max_messages, Relaxed);
+        self.crate_version.store(crate::crate_version(), Relaxed);
+        self.canary.store(crate::HEADER_CANARY, Relaxed);
 
         Ok(())
     }
@@ -91,25 +193,62 @@ impl View {
 
 pub struct Buffer {
     map: MmapMut,
+    // Cached pointer to the header at the beginning of `map`, which remains valid (and at the same address) for
+    // as long as `map` is alive, even if this struct is moved.
+    header: *const Header,
+    path: String,
+    // Value of `Header::generation` when this mapping was created (see `Receiver::resize`).
+    generation: u32,
+    // Mapping this one replaced, if any, kept alive along with the temporary file it may own (see
+    // `Receiver::resize`).
+    _previous: Option<View>,
     _file: Option<NamedTempFile>,
 }
 
 impl Buffer {
-    pub fn try_new(_path: &str, map: MmapMut, file: Option<NamedTempFile>) -> Result<Self> {
-        Ok(Buffer { map, _file: file })
+    pub fn try_new(path: &str, map: MmapMut, file: Option<NamedTempFile>) -> Result<Self> {
+        if map.len() < mem::size_of::<Header>() {
+            return Err(Error::IncompatibleRingBuffer);
+        }
+
+        #[allow(clippy::cast_ptr_alignment)]
+        let generation = unsafe { (*(map.as_ptr() as *const Header)).generation.load(Relaxed) };
+
+        Ok(Buffer {
+            #[allow(clippy::cast_ptr_alignment)]
+            header: map.as_ptr() as *const Header,
+            map,
+            path: path.to_owned(),
+            generation,
+            _previous: None,
+            _file: file,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn keep_alive(&mut self, previous: View) {
+        self._previous = Some(previous);
     }
 
     pub fn header(&self) -> &Header {
-        #[allow(clippy::cast_ptr_alignment)]
-        unsafe {
-            &*(self.map.as_ptr() as *const Header)
-        }
+        unsafe { &*self.header }
     }
 
     pub fn lock(&self) -> Result<Lock> {
         Lock::try_new(self)
     }
 
+    pub fn lock_timeout(&self, timeout: Option<Duration>) -> Result<Lock<'_>> {
+        Lock::try_new_timeout(self, timeout)
+    }
+
     pub fn map(&self) -> &MmapMut {
         &self.map
     }
@@ -119,16 +258,109 @@ impl Buffer {
     }
 }
 
+/// Clock used by the condition variable for timed waits.
+///
+/// `CLOCK_MONOTONIC` is unaffected by changes to the system clock (e.g. NTP steps), so timeouts are honored
+/// accurately.  Apple platforms lack `pthread_condattr_setclock`, so we have to make do with `CLOCK_REALTIME` there.
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+const CONDITION_CLOCK: libc::clockid_t = libc::CLOCK_MONOTONIC;
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const CONDITION_CLOCK: libc::clockid_t = libc::CLOCK_REALTIME;
+
+/// Returns the current time according to `clock`, or zero if it is somehow negative (e.g. the system clock is set
+/// before the Unix epoch).
+fn now(clock: libc::clockid_t) -> Duration {
+    let mut now = MaybeUninit::<libc::timespec>::uninit();
+    let now = unsafe {
+        libc::clock_gettime(clock, now.as_mut_ptr());
+        now.assume_init()
+    };
+
+    if now.tv_sec < 0 {
+        Duration::default()
+    } else {
+        Duration::new(now.tv_sec as u64, now.tv_nsec as u32)
+    }
+}
+
+/// Converts a relative timeout to an absolute deadline according to `clock`, e.g. `CLOCK_REALTIME` for
+/// `pthread_mutex_timedlock` or `CONDITION_CLOCK` for `pthread_cond_timedwait`.
+///
+/// The result is always valid (i.e. `tv_sec` is non-negative and `tv_nsec` is in `[0, 1e9)`), even if the system
+/// clock is set before the Unix epoch or the deadline is too far in the future to represent, so that clock skew
+/// can't cause `pthread_cond_timedwait` to fail with `EINVAL`.
+#[allow(clippy::cast_lossless)]
+pub fn deadline(clock: libc::clockid_t, timeout: Duration) -> libc::timespec {
+    let then = now(clock)
+        .checked_add(timeout)
+        .unwrap_or_else(|| Duration::from_secs(u64::MAX));
+
+    libc::timespec {
+        // Clamp rather than wrap if the deadline is beyond what `time_t` can represent (e.g. on 32-bit platforms):
+        tv_sec: then.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+        tv_nsec: then.subsec_nanos() as c_long,
+    }
+}
+
+/// Returns the current time in milliseconds according to `CLOCK_MONOTONIC`, which is shared by all processes on
+/// the system and unaffected by changes to the system clock.
+pub fn monotonic_millis() -> u64 {
+    now(libc::CLOCK_MONOTONIC).as_millis() as u64
+}
+
+/// Attempt to lock `mutex`, waiting for up to `timeout`, and return zero on success or an error number otherwise.
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+unsafe fn timed_lock(mutex: *mut libc::pthread_mutex_t, timeout: Duration) -> i32 {
+    if timeout == Duration::from_secs(0) {
+        libc::pthread_mutex_trylock(mutex)
+    } else {
+        libc::pthread_mutex_timedlock(mutex, &deadline(libc::CLOCK_REALTIME, timeout))
+    }
+}
+
+/// Apple platforms lack `pthread_mutex_timedlock`, so we poll using `pthread_mutex_trylock` instead.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+unsafe fn timed_lock(mutex: *mut libc::pthread_mutex_t, timeout: Duration) -> i32 {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let result = libc::pthread_mutex_trylock(mutex);
+        if result != libc::EBUSY || Instant::now() >= deadline {
+            break result;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
 pub struct Lock<'a>(&'a Buffer);
 
 impl<'a> Lock<'a> {
     pub fn try_new(buffer: &Buffer) -> Result<Lock> {
         unsafe {
-            nonzero!(libc::pthread_mutex_lock(buffer.header().mutex.get()))?;
+            let mutex = buffer.header().mutex.get();
+            nonzero!(owner_dead_ok(mutex, libc::pthread_mutex_lock(mutex)))?;
         }
         Ok(Lock(buffer))
     }
 
+    /// Like [`try_new`](#method.try_new), but give up with `Err(Error::LockTimeout)` if the lock can't be acquired
+    /// within `timeout` (if specified).
+    pub fn try_new_timeout(buffer: &Buffer, timeout: Option<Duration>) -> Result<Lock<'_>> {
+        let timeout = match timeout {
+            Some(timeout) => timeout,
+            None => return Self::try_new(buffer),
+        };
+
+        unsafe {
+            let mutex = buffer.header().mutex.get();
+            match owner_dead_ok(mutex, timed_lock(mutex, timeout)) {
+                0 => Ok(Lock(buffer)),
+                libc::ETIMEDOUT | libc::EBUSY => Err(Error::LockTimeout),
+                error => Err(Error::Runtime(format!("acquiring mutex failed: {}", error))),
+            }
+        }
+    }
+
     pub fn notify_all(&mut self) -> Result<()> {
         unsafe {
             nonzero!(libc::pthread_cond_broadcast(
@@ -137,35 +369,31 @@ impl<'a> Lock<'a> {
         }
     }
 
+    pub fn notify_one(&mut self) -> Result<()> {
+        unsafe { nonzero!(libc::pthread_cond_signal(self.0.header().condition.get())) }
+    }
+
     pub fn wait(&mut self, _view: &View) -> Result<()> {
         unsafe {
-            nonzero!(libc::pthread_cond_wait(
-                self.0.header().condition.get(),
-                self.0.header().mutex.get()
+            let mutex = self.0.header().mutex.get();
+            nonzero!(owner_dead_ok(
+                mutex,
+                libc::pthread_cond_wait(self.0.header().condition.get(), mutex)
             ))
         }
     }
 
-    #[allow(clippy::cast_lossless)]
     pub fn timed_wait(&mut self, view: &View, timeout: Option<Duration>) -> Result<()> {
         if let Some(timeout) = timeout {
-            let then = SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                + timeout;
-
-            let then = libc::timespec {
-                tv_sec: then.as_secs() as libc::time_t,
-                tv_nsec: then.subsec_nanos() as c_long,
-            };
+            let then = deadline(CONDITION_CLOCK, timeout);
 
             let timeout_ok = |result| if result == libc::ETIMEDOUT { 0 } else { result };
 
             unsafe {
-                nonzero!(timeout_ok(libc::pthread_cond_timedwait(
-                    self.0.header().condition.get(),
-                    self.0.header().mutex.get(),
-                    &then
+                let mutex = self.0.header().mutex.get();
+                nonzero!(timeout_ok(owner_dead_ok(
+                    mutex,
+                    libc::pthread_cond_timedwait(self.0.header().condition.get(), mutex, &then)
                 )))
             }
         } else {
@@ -184,16 +412,30 @@ impl<'a> Drop for Lock<'a> {
 
 #[cfg(any(test, feature = "fork"))]
 pub mod test {
+    use crate::ChildError;
     use anyhow::{anyhow, Result};
     use errno::errno;
     use std::{
+        any::Any,
         ffi::c_void,
         io::Write,
         os::raw::c_int,
+        panic::{self, AssertUnwindSafe},
         process,
         thread::{self, JoinHandle},
     };
 
+    /// Exit status used by the child when its function returns an error
+    const EXIT_FAILED: c_int = 1;
+
+    /// Exit status used by the child when its function panics (matching the status Rust uses for a panic in
+    /// `main`)
+    const EXIT_PANICKED: c_int = 101;
+
+    /// Maximum length of the message the child reports to the parent.  This is less than `PIPE_BUF`, so the child
+    /// never blocks writing it.
+    const MAX_MESSAGE: usize = 512;
+
     struct Descriptor(c_int);
 
     impl Descriptor {
@@ -217,6 +459,15 @@ pub mod test {
                 }
             }
         }
+
+        fn report(&self, message: &str) {
+            let mut end = message.len().min(MAX_MESSAGE);
+            while !message.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            unsafe { libc::write(self.0, message.as_ptr() as *const c_void, end) };
+        }
     }
 
     impl Drop for Descriptor {
@@ -234,11 +485,59 @@ pub mod test {
         Ok((Descriptor(fds[0]), Descriptor(fds[1])))
     }
 
+    fn panic_message(payload: &(dyn Any + Send)) -> String {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            (*message).to_owned()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "<non-string panic payload>".to_owned()
+        }
+    }
+
+    /// Decode the status reported by `waitpid` for a child which reported `message` and wrote `stderr`.
+    fn outcome(status: c_int, message: String, stderr: String) -> Result<(), ChildError> {
+        if libc::WIFEXITED(status) {
+            match libc::WEXITSTATUS(status) {
+                0 => Ok(()),
+                EXIT_FAILED if !message.is_empty() => Err(ChildError::Failed { message, stderr }),
+                EXIT_PANICKED if !message.is_empty() => {
+                    Err(ChildError::Panicked { message, stderr })
+                }
+                status => Err(ChildError::Exited { status, stderr }),
+            }
+        } else if libc::WIFSIGNALED(status) {
+            Err(ChildError::Signaled {
+                signal: libc::WTERMSIG(status),
+                stderr,
+            })
+        } else {
+            Err(ChildError::Monitor(format!(
+                "unexpected waitpid status: {}",
+                status
+            )))
+        }
+    }
+
+    /// Run `fun` in a child process, returning a handle to a thread in this process which waits for the child to
+    /// exit and reports how it did so.
+    ///
+    /// The child's stdout is discarded, and its stderr is captured and included in any error.  This process's copy
+    /// of `fun` (including anything it captured) is dropped once the child exits.
     pub fn fork<F: Send + 'static + FnOnce() -> Result<()>>(
         fun: F,
-    ) -> Result<JoinHandle<Result<()>>> {
+    ) -> Result<JoinHandle<Result<(), ChildError>>> {
+        Ok(thread::spawn(fork_0(fun)?))
+    }
+
+    /// Run `fun` in a child process, returning a function which blocks until the child exits and reports how it
+    /// did so.  The caller is responsible for calling it on a suitable thread.
+    pub(crate) fn fork_0<F: Send + 'static + FnOnce() -> Result<()>>(
+        fun: F,
+    ) -> Result<impl FnOnce() -> Result<(), ChildError> + Send + 'static> {
         let (_, out_tx) = pipe()?;
         let (err_rx, err_tx) = pipe()?;
+        let (message_rx, message_tx) = pipe()?;
         let (alive_rx, alive_tx) = pipe()?;
 
         match unsafe { libc::fork() } {
@@ -270,46 +569,52 @@ pub mod test {
                     return Err(anyhow!("dup2 failed; errno: {}", errno()));
                 }
 
-                if let Err(e) = fun() {
-                    eprintln!("{:?}", e);
-                    process::exit(1);
-                } else {
-                    process::exit(0);
+                match panic::catch_unwind(AssertUnwindSafe(fun)) {
+                    Ok(Ok(())) => process::exit(0),
+                    Ok(Err(e)) => {
+                        message_tx.report(&format!("{:#}", e));
+                        process::exit(EXIT_FAILED);
+                    }
+                    Err(payload) => {
+                        message_tx.report(&panic_message(payload.as_ref()));
+                        process::exit(EXIT_PANICKED);
+                    }
                 }
             }
             pid => {
-                // I'm the parent process -- spawn a thread to monitor the child
+                // I'm the parent process -- return a function to monitor the child
+
+                drop(message_tx);
 
-                Ok(thread::spawn(move || {
+                Ok(move || {
                     let _alive_tx = alive_tx;
+                    // Keep our copy of anything `fun` captured until the child exits, since e.g. a `Sender` is only
+                    // counted on behalf of this process (see `Sender`).
+                    let _fun = fun;
                     let mut stderr = Vec::<u8>::new();
-                    err_rx.forward(&mut stderr)?;
+                    err_rx
+                        .forward(&mut stderr)
+                        .map_err(|e| ChildError::Monitor(e.to_string()))?;
+
+                    let mut message = Vec::<u8>::new();
+                    message_rx
+                        .forward(&mut message)
+                        .map_err(|e| ChildError::Monitor(e.to_string()))?;
 
                     let mut status = 0;
                     if -1 == unsafe { libc::waitpid(pid, &mut status, 0) } {
-                        return Err(anyhow!("waitpid failed; errno: {}", errno()));
+                        return Err(ChildError::Monitor(format!(
+                            "waitpid failed; errno: {}",
+                            errno()
+                        )));
                     }
 
-                    if libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0 {
-                        Ok(())
-                    } else {
-                        Err(anyhow!(
-                            "child exited{}{}",
-                            if libc::WIFEXITED(status) {
-                                format!(" (exit status {})", libc::WEXITSTATUS(status))
-                            } else if libc::WIFSIGNALED(status) {
-                                format!(" (killed by signal {})", libc::WTERMSIG(status))
-                            } else {
-                                String::new()
-                            },
-                            if stderr.is_empty() {
-                                String::new()
-                            } else {
-                                format!("; stderr:\n{}", String::from_utf8_lossy(&stderr))
-                            }
-                        ))
-                    }
-                }))
+                    outcome(
+                        status,
+                        String::from_utf8_lossy(&message).into_owned(),
+                        String::from_utf8_lossy(&stderr).into_owned(),
+                    )
+                })
             }
         }
     }
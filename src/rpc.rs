@@ -0,0 +1,269 @@
+use crate::{Error, Receiver, Result, Sender, SharedRingBuffer, ShutdownToken};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+
+/// Longest a [`Client`](struct.Client.html) waits for a reply before checking whether the server is still alive.
+const LIVENESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Replies received by a [`Client`](struct.Client.html) but not yet claimed by the call they belong to.
+struct Replies {
+    receiver: Receiver,
+    pending: HashMap<u64, Vec<u8>>,
+}
+
+/// Calling end of a request/reply channel, which sends each request to a [`Server`](struct.Server.html) and
+/// blocks until the matching reply arrives.
+///
+/// Requests are sent on a ring buffer shared by every client of the server, along with a correlation ID and the
+/// path of a ring buffer private to this client, on which the server sends the reply.  Calls may be made from
+/// several threads at once, in which case only one thread at a time waits on the reply ring buffer, setting aside
+/// replies to the others' calls until they claim them.  Replies which arrive after their call has timed out are
+/// discarded.
+///
+/// If the server's [`Receiver`](struct.Receiver.html) is dropped while a call is waiting (e.g. because the server
+/// process exited or restarted), the call returns
+/// `Err(`[`Error::NoReceiver`](enum.Error.html#variant.NoReceiver)`)`, since the request may or may not have been
+/// handled.  The next call opens the request ring buffer again by path, so it reaches a server which has since
+/// restarted, whether it created a new file or recreated the old one in place.  A server which crashes without
+/// running destructors can't be detected this way, so calls which must not block indefinitely should use
+/// [`Client::call_timeout`](struct.Client.html#method.call_timeout).
+pub struct Client {
+    path: String,
+    reply_to: String,
+    requests: Mutex<Sender>,
+    replies: Mutex<Replies>,
+    // Held so the reply ring buffer never appears to have no senders, e.g. while a server restarts (see
+    // `Error::NoSenders`).
+    _reply_sender: Sender,
+    outstanding: Mutex<HashSet<u64>>,
+    next_id: AtomicU64,
+}
+
+impl Client {
+    /// Constructs a [`Client`](struct.Client.html) which sends requests to the [`Server`](struct.Server.html)
+    /// for the ring buffer at `path`, creating a temporary ring buffer of `size_in_bytes` to receive replies on.
+    pub fn new(path: &str, size_in_bytes: u32) -> Result<Self> {
+        let requests = Sender::new(SharedRingBuffer::open(path)?);
+        let (reply_to, buffer) = SharedRingBuffer::create_temp(size_in_bytes)?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            reply_to,
+            requests: Mutex::new(requests),
+            _reply_sender: Sender::new(buffer.clone()),
+            replies: Mutex::new(Replies {
+                receiver: Receiver::new(buffer),
+                pending: HashMap::new(),
+            }),
+            outstanding: Mutex::new(HashSet::new()),
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// Send `request` to the server and block until it replies.
+    pub fn call<T, R>(&self, request: &T) -> Result<R>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.call_0(request, None)
+            .map(|reply| reply.expect("should not fail -- calls without a deadline can't time out"))
+    }
+
+    /// Send `request` to the server and block for up to the specified duration until it replies, returning
+    /// `Ok(None)` if it didn't reply in time.
+    ///
+    /// The duration includes any time spent waiting for space in the request ring buffer.  A request which times
+    /// out may still be handled by the server, but its reply will be discarded.
+    pub fn call_timeout<T, R>(&self, request: &T, timeout: Duration) -> Result<Option<R>>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        self.call_0(request, Instant::now().checked_add(timeout))
+    }
+
+    fn call_0<T, R>(&self, request: &T, deadline: Option<Instant>) -> Result<Option<R>>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        let id = self.next_id.fetch_add(1, Relaxed);
+
+        lock(&self.outstanding)?.insert(id);
+
+        let result = self.send_request(id, request, deadline).and_then(|sent| {
+            if sent {
+                self.recv_reply(id, deadline)
+            } else {
+                Ok(None)
+            }
+        });
+
+        lock(&self.outstanding)?.remove(&id);
+        // The reply may have arrived while another thread was waiting, after we gave up on it.
+        lock(&self.replies)?.pending.remove(&id);
+
+        Ok(match result? {
+            Some(bytes) => Some(bincode::deserialize(&bytes)?),
+            None => None,
+        })
+    }
+
+    /// Send `request` tagged with `id`, returning `Ok(false)` if `deadline` passed while waiting for space.
+    fn send_request<T: Serialize>(
+        &self,
+        id: u64,
+        request: &T,
+        deadline: Option<Instant>,
+    ) -> Result<bool> {
+        let mut requests = lock(&self.requests)?;
+
+        if server_gone(&requests) {
+            // The server may have restarted, so pick up its new ring buffer if so.
+            *requests = Sender::new(SharedRingBuffer::open(&self.path)?);
+        }
+
+        let message = (id, &self.reply_to, request);
+        if let Some(deadline) = deadline {
+            requests.send_timeout(&message, deadline.saturating_duration_since(Instant::now()))
+        } else {
+            requests.send(&message).map(|()| true)
+        }
+    }
+
+    /// Wait for the reply to the request tagged with `id`, setting aside replies to other outstanding requests.
+    fn recv_reply(&self, id: u64, deadline: Option<Instant>) -> Result<Option<Vec<u8>>> {
+        loop {
+            let mut replies = lock(&self.replies)?;
+
+            if let Some(bytes) = replies.pending.remove(&id) {
+                return Ok(Some(bytes));
+            }
+
+            let now = Instant::now();
+            let timeout = match deadline {
+                Some(deadline) if deadline <= now => return Ok(None),
+                Some(deadline) => cmp::min(deadline - now, LIVENESS_INTERVAL),
+                None => LIVENESS_INTERVAL,
+            };
+
+            match replies.receiver.recv_timeout::<(u64, Vec<u8>)>(timeout)? {
+                Some((reply_id, bytes)) if reply_id == id => return Ok(Some(bytes)),
+                Some((reply_id, bytes)) => {
+                    if lock(&self.outstanding)?.contains(&reply_id) {
+                        replies.pending.insert(reply_id, bytes);
+                    }
+                }
+                None => {
+                    if server_gone(&*lock(&self.requests)?) {
+                        return Err(Error::NoReceiver);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Serving end of a request/reply channel, which receives requests from any number of
+/// [`Client`](struct.Client.html)s and sends each reply to the client which made the request.
+///
+/// The server holds a [`Sender`](struct.Sender.html) for its own ring buffer, so it keeps serving as clients come
+/// and go, until the ring buffer is shut down using the token returned by
+/// [`Server::shutdown_token`](struct.Server.html#method.shutdown_token).
+///
+/// The server keeps a [`Sender`](struct.Sender.html) for the reply ring buffer of each client it has replied to,
+/// and drops those belonging to clients which have since gone away each time a new client makes its first call, so
+/// clients which come and go don't accumulate.
+pub struct Server {
+    receiver: Receiver,
+    sender: Sender,
+    pub(crate) clients: HashMap<String, Sender>,
+}
+
+impl Server {
+    /// Constructs a [`Server`](struct.Server.html) which receives requests on the specified
+    /// [`SharedRingBuffer`](struct.SharedRingBuffer.html), e.g. one created using
+    /// [`SharedRingBuffer::create`](struct.SharedRingBuffer.html#method.create) at a path known to clients.
+    pub fn new(buffer: SharedRingBuffer) -> Self {
+        Self {
+            sender: Sender::new(buffer.clone()),
+            receiver: Receiver::new(buffer),
+            clients: HashMap::new(),
+        }
+    }
+
+    /// Returns a [`ShutdownToken`](struct.ShutdownToken.html) which may be used to stop
+    /// [`Server::serve`](struct.Server.html#method.serve), e.g. from another thread.
+    pub fn shutdown_token(&self) -> ShutdownToken {
+        self.sender.shutdown_token()
+    }
+
+    /// Receive requests, blocking while waiting for each one, and reply to each with the result of calling
+    /// `handler` on it, until the ring buffer is shut down.
+    ///
+    /// This returns `Ok(())` once the ring buffer has been shut down and every request already sent has been
+    /// handled, or an error if receiving a request or sending a reply fails.  Replies to clients which have gone
+    /// away are discarded, but replying blocks while a client's reply ring buffer is full, e.g. because it has
+    /// stopped making calls after several timed out.
+    pub fn serve<T, R>(&mut self, mut handler: impl FnMut(T) -> R) -> Result<()>
+    where
+        T: for<'de> Deserialize<'de>,
+        R: Serialize,
+    {
+        loop {
+            let (id, reply_to, request) = match self.receiver.recv::<(u64, String, T)>() {
+                Ok(message) => message,
+                Err(Error::Interrupted) => break Ok(()),
+                Err(e) => break Err(e),
+            };
+
+            let reply = bincode::serialize(&handler(request))?;
+
+            self.reply(id, reply_to, reply)?;
+        }
+    }
+
+    /// Send `reply` to the client with the reply ring buffer at `reply_to`, if it is still there.
+    fn reply(&mut self, id: u64, reply_to: String, reply: Vec<u8>) -> Result<()> {
+        if !self.clients.contains_key(&reply_to) {
+            // Clients usually go away right after their last reply, which isn't noticed below, so forget any which
+            // have before remembering another.
+            self.clients.retain(|_, client| client.is_receiver_alive());
+
+            if let Ok(buffer) = SharedRingBuffer::open(&reply_to) {
+                self.clients.insert(reply_to.clone(), Sender::new(buffer));
+            } else {
+                // The client has gone away, taking its reply ring buffer with it.
+                return Ok(());
+            }
+        }
+
+        if self.clients[&reply_to].is_receiver_alive() {
+            self.clients[&reply_to].send(&(id, reply))
+        } else {
+            self.clients.remove(&reply_to);
+            Ok(())
+        }
+    }
+}
+
+/// Returns `true` if the server which received requests sent using `requests` has gone away, either dropping its
+/// [`Receiver`](struct.Receiver.html) or having been replaced by another which recreated the ring buffer in place.
+fn server_gone(requests: &Sender) -> bool {
+    !requests.is_receiver_alive() || requests.buffer_id != requests.buffer.buffer_id()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|_| Error::Runtime("Client lock poisoned".into()))
+}
@@ -0,0 +1,60 @@
+use crate::{Config, Error, Result};
+use serde::Deserialize;
+
+/// Message type which carries a fingerprint of its schema, for use with
+/// [`Sender::send_checked`](struct.Sender.html#method.send_checked) and
+/// [`Receiver::recv_checked`](struct.Receiver.html#method.recv_checked).
+///
+/// The fingerprint should change whenever the type's serialized layout does (e.g. when a field is added, removed,
+/// or retyped), so that a receiver built against a different version of the type reports
+/// [`Error::SchemaMismatch`](enum.Error.html#variant.SchemaMismatch) rather than silently misinterpreting the
+/// message.  [`schema_hash`](fn.schema_hash.html) may be used to derive it from a description of the type, e.g.:
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Serialize, Deserialize)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// impl ipmpsc::Schema for Point {
+///     const SCHEMA_HASH: u64 = ipmpsc::schema_hash("Point { x: i32, y: i32 }");
+/// }
+/// ```
+pub trait Schema {
+    /// Fingerprint of this type's schema
+    const SCHEMA_HASH: u64;
+}
+
+/// Compute a 64-bit FNV-1a hash of the specified schema description, suitable for use as a
+/// [`Schema::SCHEMA_HASH`](trait.Schema.html#associatedconstant.SCHEMA_HASH).
+pub const fn schema_hash(description: &str) -> u64 {
+    let bytes = description.as_bytes();
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut index = 0;
+    while index < bytes.len() {
+        hash ^= bytes[index] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        index += 1;
+    }
+    hash
+}
+
+/// Verify that the schema fingerprint at the beginning of `bytes` matches `T`'s, and if so deserialize the
+/// remainder as a `T`, both using `config`.
+pub(crate) fn deserialize_checked<'a, T: Schema + Deserialize<'a>>(
+    config: Config,
+    bytes: &'a [u8],
+) -> Result<T> {
+    let (found, rest) = config.deserialize_prefix::<u64>(bytes)?;
+
+    if found == T::SCHEMA_HASH {
+        config.deserialize(rest)
+    } else {
+        Err(Error::SchemaMismatch {
+            found,
+            expected: T::SCHEMA_HASH,
+        })
+    }
+}
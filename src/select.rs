@@ -0,0 +1,93 @@
+use crate::{Receiver, Result};
+use std::{
+    cell::Cell,
+    cmp, thread,
+    time::{Duration, Instant},
+};
+
+/// Shortest time to sleep between polls while waiting for a receiver to become ready.
+const MIN_POLL_INTERVAL: Duration = Duration::from_micros(50);
+
+/// Longest time to sleep between polls while waiting for a receiver to become ready, which bounds the latency of
+/// noticing a message once the selector has been waiting for a while.
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Waits for any of several [`Receiver`](struct.Receiver.html)s to become ready, similar to `crossbeam`'s
+/// `select!`.
+///
+/// A receiver is ready if a message is available or a receive would fail immediately (e.g. with
+/// [`Error::NoSenders`](enum.Error.html#variant.NoSenders)), so the caller should follow a successful selection
+/// with a non-blocking receive such as [`Receiver::try_recv`](struct.Receiver.html#method.try_recv).
+///
+/// Each ring buffer has its own lock and condition variable, so there is nothing a single thread can block on for
+/// all of them at once.  Instead, the receivers are polled, sleeping with exponential backoff (up to a few
+/// milliseconds) between polls.  Polling resumes where the previous selection left off, so a busy receiver can't
+/// starve the ones after it.
+pub struct Selector<'a> {
+    receivers: Vec<&'a Receiver>,
+    next: Cell<usize>,
+}
+
+impl<'a> Selector<'a> {
+    /// Constructs a [`Selector`](struct.Selector.html) over the specified receivers, which are identified by their
+    /// indexes in `receivers` when selected.
+    pub fn new(receivers: &[&'a Receiver]) -> Self {
+        Self {
+            receivers: receivers.to_vec(),
+            next: Cell::new(0),
+        }
+    }
+
+    /// Returns the index of a ready receiver, or `Ok(None)` if none are ready, without blocking.
+    pub fn try_select(&self) -> Result<Option<usize>> {
+        let count = self.receivers.len();
+
+        for offset in 0..count {
+            let index = (self.next.get() + offset) % count;
+
+            // Errors are reported to the caller by the receive which follows the selection:
+            if !matches!(self.receivers[index].try_recv_raw_0(), Ok(None)) {
+                self.next.set((index + 1) % count);
+                return Ok(Some(index));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the index of a ready receiver, blocking if necessary until one becomes ready.
+    pub fn select(&self) -> Result<usize> {
+        loop {
+            if let Some(index) = self.select_timeout(Duration::from_secs(u64::MAX))? {
+                return Ok(index);
+            }
+        }
+    }
+
+    /// Returns the index of a ready receiver, blocking for up to the specified duration if necessary, or `Ok(None)`
+    /// if none became ready in that time.
+    pub fn select_timeout(&self, timeout: Duration) -> Result<Option<usize>> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut interval = MIN_POLL_INTERVAL;
+
+        loop {
+            if let Some(index) = self.try_select()? {
+                return Ok(Some(index));
+            }
+
+            let sleep = if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Ok(None);
+                }
+                cmp::min(interval, deadline - now)
+            } else {
+                interval
+            };
+
+            thread::sleep(sleep);
+
+            interval = cmp::min(interval * 2, MAX_POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{
+    check_header, count_frames, debug_check_pointers, frame_size, notify_data_ready, region, stamp,
+    write_frame, Error, Result, SharedRingBuffer, WaitingSender, BEGINNING, CONFIG_FINGERPRINT,
+    OPTION_SINGLE_PRODUCER, OPTION_ZERO_SIZED,
+};
+use serde::Serialize;
+use std::{
+    process,
+    sync::atomic::Ordering::{Relaxed, Release, SeqCst},
+    time::{Duration, Instant},
+};
+
+/// Represents the sending end of a single-producer, single-consumer channel.
+///
+/// Unlike [`Sender`](struct.Sender.html), this type publishes messages using only atomic operations on the read
+/// and write pointers, acquiring the ring buffer lock only when it must wait for space or wake a receiver which is
+/// waiting on the lock (futex waits need no lock).  This is only sound if it is the sole sender, so it may only be
+/// used with a ring buffer created using
+/// [`SharedRingBufferBuilder::single_producer`](struct.SharedRingBufferBuilder.html#method.single_producer), and
+/// at most one instance may exist for a given ring buffer at a time, across all processes.
+///
+/// The receiving end is an ordinary [`Receiver`](struct.Receiver.html) (or
+/// [`AsyncReceiver`](struct.AsyncReceiver.html)), which must use the default [`Config`](struct.Config.html).
+pub struct SpscSender {
+    buffer: SharedRingBuffer,
+    buffer_id: u64,
+    // ID of the process which counted this sender in `Header::sender_count` (see `Sender`).
+    pid: u32,
+}
+
+impl SpscSender {
+    /// Constructs an [`SpscSender`](struct.SpscSender.html) from the specified
+    /// [`SharedRingBuffer`](struct.SharedRingBuffer.html).
+    ///
+    /// This will return `Err(`[`Error::WrongSenderKind`](enum.Error.html#variant.WrongSenderKind)`)` if the ring
+    /// buffer was not created for a single producer, or
+    /// `Err(`[`Error::TooManySenders`](enum.Error.html#variant.TooManySenders)`)` if another sender is currently
+    /// connected to it.
+    pub fn new(buffer: SharedRingBuffer) -> Result<Self> {
+        let header = buffer.0.buffer().header();
+
+        if header.options.load(Relaxed) & OPTION_SINGLE_PRODUCER == 0 {
+            return Err(Error::WrongSenderKind);
+        }
+
+        header
+            .sender_count
+            .compare_exchange(0, 1, Relaxed, Relaxed)
+            .map_err(|_| Error::TooManySenders)?;
+        header.sender_connected.store(1, Release);
+
+        stamp(&header.sender_heartbeat);
+
+        Ok(Self {
+            buffer_id: buffer.buffer_id(),
+            buffer,
+            pid: process::id(),
+        })
+    }
+
+    /// Send the specified message, waiting for sufficient contiguous space to become available in the ring buffer
+    /// if necessary.
+    ///
+    /// See [`Sender::send`](struct.Sender.html#method.send) for details.
+    pub fn send(&self, value: &impl Serialize) -> Result<()> {
+        self.send_timeout_0(value, None).map(drop)
+    }
+
+    /// Send the specified message, waiting for sufficient contiguous space to become available in the ring buffer
+    /// if necessary, but only up to the specified timeout.
+    ///
+    /// See [`Sender::send_timeout`](struct.Sender.html#method.send_timeout) for details.
+    pub fn send_timeout(&self, value: &impl Serialize, timeout: Duration) -> Result<bool> {
+        self.send_timeout_0(value, Some(timeout))
+    }
+
+    /// Attempt to send the specified message without waiting for space, returning `Ok(false)` if there isn't
+    /// enough.
+    ///
+    /// See [`Sender::try_send`](struct.Sender.html#method.try_send) for details.
+    pub fn try_send(&self, value: &impl Serialize) -> Result<bool> {
+        self.send_timeout(value, Duration::from_secs(0))
+    }
+
+    fn send_timeout_0(&self, value: &impl Serialize, timeout: Option<Duration>) -> Result<bool> {
+        let buffer = self.buffer.0.buffer();
+        let header = buffer.header();
+        let map = self.buffer.0.map_mut();
+
+        debug_assert_eq!(
+            self.buffer_id,
+            self.buffer.buffer_id(),
+            "sender used with wrong ring buffer"
+        );
+
+        check_header(header)?;
+
+        let size = bincode::serialized_size(value)? as u32;
+
+        if size == 0 && header.options.load(Relaxed) & OPTION_ZERO_SIZED == 0 {
+            return Err(Error::ZeroSizedMessage);
+        }
+
+        let frame_size = frame_size(size).ok_or(Error::MessageTooLarge)?;
+        let map_len = map.len();
+
+        if BEGINNING as u64 + frame_size as u64 + 4 > map_len as u64 {
+            return Err(Error::MessageTooLarge);
+        }
+
+        let max_messages = header.max_messages.load(Relaxed);
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut waiting = None;
+        let mut write;
+        loop {
+            // Only this sender moves the write pointer, while the receiver may advance the read pointer at any time.
+            // A stale read pointer only makes us more conservative, since it never moves past the write pointer.
+            write = header.write.load(Relaxed);
+            let read = header.read.load(SeqCst);
+
+            debug_check_pointers(read, write, map_len);
+
+            let full = max_messages != 0 && count_frames(map, read, write)? >= max_messages;
+
+            if full {
+                // Wait for the receiver to consume a message (see below).
+            } else if write >= read {
+                if (write + frame_size + 4) as usize <= map_len {
+                    break;
+                } else if read != BEGINNING {
+                    assert!(write > BEGINNING);
+
+                    bincode::serialize_into(region(map, write, write + 4)?, &0_u32)?;
+                    header.wrap_count.fetch_add(1, Relaxed);
+                    self.publish(BEGINNING)?;
+                    continue;
+                }
+            } else if write + frame_size + 4 <= read {
+                break;
+            }
+
+            if waiting.is_none() {
+                // Register as a waiter and then check again before blocking, as with `Sender`.
+                waiting = Some(WaitingSender::new(header));
+                continue;
+            }
+
+            let remaining = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if remaining > Duration::from_secs(0) => Some(remaining),
+                    _ => return Ok(false),
+                },
+                None => None,
+            };
+
+            // The receiver notifies waiting senders while holding the lock after advancing the read pointer, so
+            // checking it again here ensures we won't miss the notification.
+            let mut lock = buffer.lock_timeout(remaining)?;
+            if header.read.load(SeqCst) == read {
+                lock.timed_wait(&self.buffer.0, remaining)?;
+            }
+        }
+
+        drop(waiting);
+
+        write_frame(
+            region(map, write, write + frame_size)?,
+            size,
+            CONFIG_FINGERPRINT,
+            |slice| Ok(bincode::serialize_into(slice, value)?),
+        )?;
+
+        header.messages_sent.fetch_add(1, Relaxed);
+        header.bytes_sent.fetch_add(frame_size.into(), Relaxed);
+
+        self.publish(write + frame_size)?;
+
+        Ok(true)
+    }
+
+    /// Store a new write pointer, making any frames preceding it visible to the receiver, and wake the receiver.
+    fn publish(&self, write: u32) -> Result<()> {
+        let buffer = self.buffer.0.buffer();
+        let header = buffer.header();
+
+        header.write.store(write, SeqCst);
+
+        // A receiver waiting on the lock checks the write pointer while holding it after registering as a waiter
+        // (see `WaitingReceiver`), so we only need the lock to notify it if it has registered.
+        if header.waiting_receivers.load(SeqCst) != 0 {
+            buffer.lock()?.notify_all()?;
+        }
+
+        notify_data_ready(buffer);
+
+        Ok(())
+    }
+}
+
+impl Drop for SpscSender {
+    fn drop(&mut self) {
+        let buffer = self.buffer.0.buffer();
+
+        // As with `Sender`, decrement the count while holding the lock (if possible) so a receiver can't miss the
+        // notification between checking the count and waiting.
+        let lock = buffer.lock();
+
+        // As with `Sender`, a copy inherited via `fork` was never counted.
+        if process::id() == self.pid {
+            buffer.header().sender_count.fetch_sub(1, Release);
+        }
+
+        if let Ok(mut lock) = lock {
+            let _ = lock.notify_all();
+        }
+
+        notify_data_ready(buffer);
+    }
+}
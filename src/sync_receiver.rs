@@ -0,0 +1,60 @@
+use crate::{Error, Receiver, Result};
+use serde::Deserialize;
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// Cheaply cloneable handle to a [`Receiver`](struct.Receiver.html) which may be shared among threads in a single
+/// process, e.g. to drive a single logical consumer from a thread pool.
+///
+/// Each message is delivered to exactly one clone.  Receives are serialized using an in-process lock, so only one
+/// clone at a time waits on the ring buffer while the others wait for that lock.  The ring buffer's own lock can't
+/// be used for this since it is shared with senders and must not be held while waiting for a message.
+#[derive(Clone)]
+pub struct SyncReceiver(Arc<Mutex<Receiver>>);
+
+impl SyncReceiver {
+    /// Constructs a [`SyncReceiver`](struct.SyncReceiver.html) which shares the specified
+    /// [`Receiver`](struct.Receiver.html).
+    pub fn new(receiver: Receiver) -> Self {
+        Self(Arc::new(Mutex::new(receiver)))
+    }
+
+    /// Attempt to read a message without blocking on the ring buffer, as with
+    /// [`Receiver::try_recv`](struct.Receiver.html#method.try_recv).
+    ///
+    /// Note that this may still block while another clone is receiving.
+    pub fn try_recv<T>(&self) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.lock()?.try_recv()
+    }
+
+    /// Attempt to read a message, blocking if necessary until one becomes available, as with
+    /// [`Receiver::recv`](struct.Receiver.html#method.recv).
+    pub fn recv<T>(&self) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.lock()?.recv()
+    }
+
+    /// Attempt to read a message, blocking on the ring buffer for up to the specified duration if necessary, as
+    /// with [`Receiver::recv_timeout`](struct.Receiver.html#method.recv_timeout).
+    ///
+    /// The timeout does not include any time spent waiting for another clone to finish receiving.
+    pub fn recv_timeout<T>(&self, timeout: Duration) -> Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.lock()?.recv_timeout(timeout)
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Receiver>> {
+        self.0
+            .lock()
+            .map_err(|_| Error::Runtime("SyncReceiver lock poisoned".into()))
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{Config, Error, Result};
+use serde::Deserialize;
+
+/// Message type which carries a tag identifying it among the types sent on a channel, for use with
+/// [`Sender::send_tagged`](struct.Sender.html#method.send_tagged) and
+/// [`Receiver::recv_tagged`](struct.Receiver.html#method.recv_tagged).
+///
+/// This allows a channel to carry a mix of types in arbitrary order, with the receiver dispatching on the tag
+/// rather than relying on an implicit agreement about which type comes next.  Each type sent on a given channel
+/// must have a distinct tag, e.g.:
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// use ipmpsc::{Receiver, Tagged};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Ping(u32);
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Shutdown;
+///
+/// impl Tagged for Ping {
+///     const TAG: u16 = 1;
+/// }
+///
+/// impl Tagged for Shutdown {
+///     const TAG: u16 = 2;
+/// }
+///
+/// fn dispatch(rx: &Receiver) -> ipmpsc::Result<bool> {
+///     let message = rx.recv_tagged()?;
+///     Ok(match message.tag() {
+///         Ping::TAG => {
+///             let Ping(n) = message.deserialize()?;
+///             println!("ping {}", n);
+///             true
+///         }
+///         Shutdown::TAG => false,
+///         tag => panic!("unexpected tag {}", tag),
+///     })
+/// }
+/// ```
+///
+/// Note that a single enum type (which bincode tags with its variant index) serves the same purpose when all the
+/// message types are known in one place; this trait is useful when they are not.
+pub trait Tagged {
+    /// Tag identifying this type
+    const TAG: u16;
+}
+
+/// Message received using [`Receiver::recv_tagged`](struct.Receiver.html#method.recv_tagged), which may be
+/// deserialized once its tag has been inspected.
+#[derive(Clone, Debug)]
+pub struct TaggedMessage {
+    tag: u16,
+    bytes: Vec<u8>,
+    config: Config,
+}
+
+impl TaggedMessage {
+    /// Parse the tag from the beginning of `bytes` using `config`, which is also used to deserialize the message.
+    pub(crate) fn new(config: Config, bytes: &[u8]) -> Result<Self> {
+        let (tag, rest) = config.deserialize_prefix::<u16>(bytes)?;
+        Ok(Self {
+            tag,
+            bytes: rest.to_vec(),
+            config,
+        })
+    }
+
+    /// Returns the tag identifying the type of this message.
+    pub fn tag(&self) -> u16 {
+        self.tag
+    }
+
+    /// Deserialize this message as a `T`, returning
+    /// `Err(`[`Error::TagMismatch`](enum.Error.html#variant.TagMismatch)`)` if it was sent with a different tag.
+    pub fn deserialize<'a, T: Tagged + Deserialize<'a>>(&'a self) -> Result<T> {
+        if self.tag == T::TAG {
+            self.config.deserialize(&self.bytes)
+        } else {
+            Err(Error::TagMismatch {
+                found: self.tag,
+                expected: T::TAG,
+            })
+        }
+    }
+}
@@ -1,13 +1,13 @@
-use crate::{bitmask::BitMask, Error, Result};
+use crate::{bitmask::BitMask, Error, MutexKind, Result};
 use memmap2::MmapMut;
 use sha2::{Digest, Sha256};
 use std::{
     cell::UnsafeCell,
     convert::TryInto,
     ffi::{CStr, CString},
-    ptr, slice,
+    mem, ptr, slice,
     sync::{
-        atomic::{AtomicU32, Ordering::Relaxed},
+        atomic::{AtomicU32, AtomicU64, Ordering::Relaxed},
         Arc, Mutex,
     },
     time::Duration,
@@ -19,7 +19,7 @@ use winapi::{
         winerror,
     },
     um::{
-        errhandlingapi, handleapi, synchapi, winbase,
+        errhandlingapi, handleapi, synchapi, sysinfoapi, winbase,
         winnt::{HANDLE, LPSTR},
     },
 };
@@ -38,6 +38,12 @@ macro_rules! defer {
     };
 }
 
+/// Returns the number of milliseconds since the system was started, which is shared by all processes on the system
+/// and unaffected by changes to the system clock.
+pub fn monotonic_millis() -> u64 {
+    unsafe { sysinfoapi::GetTickCount64() }
+}
+
 fn sha256(string: &str) -> String {
     let mut hasher = Sha256::default();
 
@@ -107,10 +113,30 @@ pub struct Header {
     waiters: UnsafeCell<BitMask>,
     pub read: AtomicU32,
     pub write: AtomicU32,
+    pub receiver_alive: AtomicU32,
+    pub sender_count: AtomicU32,
+    pub sender_connected: AtomicU32,
+    pub waiting_senders: AtomicU32,
+    pub waiting_receivers: AtomicU32,
+    pub receiver_spinning: AtomicU32,
+    pub buffer_id: AtomicU64,
+    pub receiver_heartbeat: AtomicU64,
+    pub sender_heartbeat: AtomicU64,
+    pub messages_sent: AtomicU64,
+    pub bytes_sent: AtomicU64,
+    pub wrap_count: AtomicU64,
+    pub cursors: [AtomicU32; crate::MAX_BROADCAST_RECEIVERS],
+    pub generation: AtomicU32,
+    pub options: AtomicU32,
+    pub shutdown: AtomicU32,
+    pub max_messages: AtomicU32,
+    pub crate_version: AtomicU32,
+    pub canary: AtomicU64,
 }
 
 impl Header {
-    pub fn init(&self) -> Result<()> {
+    /// Note that `_mutex_kind` is ignored since we always use a named kernel mutex on Windows.
+    pub fn init(&self, _mutex_kind: MutexKind, options: u32, max_messages: u32) -> Result<()> {
         self.flags.store(crate::flags(), Relaxed);
 
         unsafe {
@@ -120,6 +146,27 @@ impl Header {
 
         self.read.store(crate::BEGINNING, Relaxed);
         self.write.store(crate::BEGINNING, Relaxed);
+        self.receiver_alive.store(0, Relaxed);
+        self.sender_count.store(0, Relaxed);
+        self.sender_connected.store(0, Relaxed);
+        self.waiting_senders.store(0, Relaxed);
+        self.waiting_receivers.store(0, Relaxed);
+        self.receiver_spinning.store(0, Relaxed);
+        self.buffer_id.store(crate::random_id(), Relaxed);
+        self.receiver_heartbeat.store(0, Relaxed);
+        self.sender_heartbeat.store(0, Relaxed);
+        self.messages_sent.store(0, Relaxed);
+        self.bytes_sent.store(0, Relaxed);
+        self.wrap_count.store(0, Relaxed);
+        for cursor in &self.cursors {
+            cursor.store(crate::broadcast::FREE, Relaxed);
+        }
+        self.generation.store(0, Relaxed);
+        self.options.store(options, Relaxed);
+        self.shutdown.store(0, Relaxed);
+        self.max_messages.store(max_messages, Relaxed);
+        self.crate_version.store(crate::crate_version(), Relaxed);
+        self.canary.store(crate::HEADER_CANARY, Relaxed);
 
         Ok(())
     }
@@ -167,6 +214,15 @@ impl Drop for View {
 
 pub struct Buffer {
     map: MmapMut,
+    // Cached pointer to the header at the beginning of `map`, which remains valid (and at the same address) for
+    // as long as `map` is alive, even if this struct is moved.
+    header: *const Header,
+    path: String,
+    // Value of `Header::generation` when this mapping was created (see `Receiver::resize`).
+    generation: u32,
+    // Mapping this one replaced, if any, kept alive along with the temporary file it may own (see
+    // `Receiver::resize`).
+    _previous: Option<View>,
     unique_id: String,
     _file: Option<NamedTempFile>,
     mutex: HANDLE,
@@ -175,8 +231,20 @@ pub struct Buffer {
 
 impl Buffer {
     pub fn try_new(path: &str, map: MmapMut, file: Option<NamedTempFile>) -> Result<Self> {
+        if map.len() < mem::size_of::<Header>() {
+            return Err(Error::IncompatibleRingBuffer);
+        }
+
+        #[allow(clippy::cast_ptr_alignment)]
+        let generation = unsafe { (*(map.as_ptr() as *const Header)).generation.load(Relaxed) };
+
         let mut buffer = Self {
+            #[allow(clippy::cast_ptr_alignment)]
+            header: map.as_ptr() as *const Header,
             map,
+            path: path.to_owned(),
+            generation,
+            _previous: None,
             // We derive the mutex and semaphore names from a hex-encoded hash of the path to ensure they're
             // unique, predictable, and contain no disallowed characters
             unique_id: sha256(path),
@@ -229,17 +297,30 @@ impl Buffer {
         Ok(semaphores[index])
     }
 
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn keep_alive(&mut self, previous: View) {
+        self._previous = Some(previous);
+    }
+
     pub fn header(&self) -> &Header {
-        #[allow(clippy::cast_ptr_alignment)]
-        unsafe {
-            &*(self.map.as_ptr() as *const Header)
-        }
+        unsafe { &*self.header }
     }
 
     pub fn lock(&self) -> Result<Lock> {
         Lock::try_new(self)
     }
 
+    pub fn lock_timeout(&self, timeout: Option<Duration>) -> Result<Lock<'_>> {
+        Lock::try_new_timeout(self, timeout)
+    }
+
     pub fn map(&self) -> &MmapMut {
         &self.map
     }
@@ -281,6 +362,26 @@ impl<'a> Lock<'a> {
         })
     }
 
+    /// Like [`try_new`](#method.try_new), but give up with `Err(Error::LockTimeout)` if the lock can't be acquired
+    /// within `timeout` (if specified).
+    pub fn try_new_timeout(buffer: &Buffer, timeout: Option<Duration>) -> Result<Lock<'_>> {
+        let milliseconds = timeout
+            .map(|timeout| timeout.as_millis().min(u128::from(winbase::INFINITE - 1)) as ULONG)
+            .unwrap_or(winbase::INFINITE);
+
+        match unsafe { synchapi::WaitForSingleObject(buffer.mutex, milliseconds) } {
+            winbase::WAIT_OBJECT_0 => Ok(Lock {
+                locked: true,
+                buffer,
+            }),
+            winerror::WAIT_TIMEOUT => Err(Error::LockTimeout),
+            _ => Err(Error::Runtime(format!(
+                "WaitForSingleObject failed: {}",
+                get_last_error()
+            ))),
+        }
+    }
+
     fn do_wait(&mut self, view: &View, milliseconds: ULONG) -> Result<()> {
         let index = view.index;
 
@@ -344,6 +445,21 @@ impl<'a> Lock<'a> {
         Ok(())
     }
 
+    pub fn notify_one(&mut self) -> Result<()> {
+        let waiters = *self.waiters();
+
+        if let Some(index) = waiters.ones().next() {
+            // See `notify_all` regarding the return value.
+            unsafe {
+                synchapi::ReleaseSemaphore(self.buffer.semaphore(index)?, 1, ptr::null_mut())
+            };
+
+            *self.waiters() = waiters.clear(index);
+        }
+
+        Ok(())
+    }
+
     fn waiters(&mut self) -> &mut BitMask {
         unsafe { &mut *self.buffer.header().waiters.get() }
     }
@@ -363,12 +479,28 @@ impl<'a> Drop for Lock<'a> {
 
 #[cfg(any(test, feature = "fork"))]
 pub mod test {
+    use crate::ChildError;
     use anyhow::Result;
     use std::thread::{self, JoinHandle};
 
+    /// Windows has no `fork`, so this runs `fun` in a thread instead.  A panic in `fun` is reported as a panic of
+    /// the returned thread rather than as `ChildError::Panicked`.
     pub fn fork<F: Send + 'static + FnOnce() -> Result<()>>(
         fun: F,
-    ) -> Result<JoinHandle<Result<()>>> {
-        Ok(thread::spawn(fun))
+    ) -> Result<JoinHandle<Result<(), ChildError>>> {
+        Ok(thread::spawn(fork_0(fun)?))
+    }
+
+    /// Returns a function which runs `fun` and reports its outcome.  The caller is responsible for calling it on a
+    /// suitable thread.
+    pub(crate) fn fork_0<F: Send + 'static + FnOnce() -> Result<()>>(
+        fun: F,
+    ) -> Result<impl FnOnce() -> Result<(), ChildError> + Send + 'static> {
+        Ok(move || {
+            fun().map_err(|e| ChildError::Failed {
+                message: format!("{:#}", e),
+                stderr: String::new(),
+            })
+        })
     }
 }